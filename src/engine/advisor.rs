@@ -0,0 +1,205 @@
+//! Which dice to keep before a re-roll, looking only at the current turn.
+//!
+//! Every keep is scored by the exact odds of what the remaining rolls can
+//! bring, with any later re-rolls chosen the best way for the same goal.
+//! Unlike the solver it does not weigh what a line is worth for the rest of
+//! the game, so it is quick enough to run on every hand.
+
+use super::combos::Combos;
+use super::triple::multiplier;
+use super::{Dice, DieFace, GameError, LineId, ScoreCardData};
+
+/// What a keep is ranked by.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Goal {
+    /// The points the hand scores on the best open line at the end of the
+    /// turn.
+    Points,
+    /// The chance of ending the turn with a hand that scores on this line.
+    Line(LineId),
+}
+
+/// One way to re-roll and what it is worth towards the goal.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Advice {
+    /// The re-roll mask for `Game::roll`: `true` for the dice to roll again.
+    pub mask: Vec<bool>,
+    /// The dice kept, lowest first.
+    pub kept: Vec<DieFace>,
+    /// Expected points for `Goal::Points`, a chance from 0 to 1 for
+    /// `Goal::Line`.
+    pub value: f64,
+}
+
+/// Every distinct keep for this hand, best first.  Masks that keep the same
+/// faces are listed once.  With no rolls left there is nothing to advise.
+/// A hand that is not a full hand of dice for the card is refused.
+pub fn advise(card: &[ScoreCardData], hand: &Dice, goal: Goal) -> Result<Vec<Advice>, GameError> {
+    if card
+        .iter()
+        .any(|column| column.rules.number_of_dice != hand.dice.len())
+    {
+        return Err(GameError::DiceCount);
+    }
+    advise_by(hand, |final_hand| hand_value(card, final_hand, goal))
+}
+
+/// Like `advise`, ranking the keeps by the expected value of the hand the
+/// turn ends with, as `value` scores it.
+pub fn advise_by<F>(hand: &Dice, value: F) -> Result<Vec<Advice>, GameError>
+where
+    F: Fn(&Dice) -> f64,
+{
+    let number_of_dice = hand.dice.len();
+    let combos = Combos::new(number_of_dice);
+    combos.index_of_faces(&hand.dice)?;
+    if hand.rolls_left <= 0 {
+        return Ok(vec![]);
+    }
+
+    let mut values = vec![0.0; combos.len()];
+    for h in combos.hands() {
+        values[h] = value(&Dice::roll_fake(combos.faces(h)));
+    }
+    let keeps = combos.keep_values_with(values, hand.rolls_left as usize);
+
+    let mut seen = vec![false; combos.len()];
+    let mut advice = vec![];
+    for bits in 0..1u32 << number_of_dice {
+        let mask: Vec<bool> = (0..number_of_dice).map(|i| bits & 1 << i != 0).collect();
+        let kept: Vec<DieFace> = hand
+            .dice
+            .iter()
+            .zip(&mask)
+            .filter(|&(_, &reroll)| !reroll)
+            .map(|(&face, _)| face)
+            .collect();
+
+        let k = combos.index_of_faces(&kept)?;
+        if seen[k] {
+            continue;
+        }
+        seen[k] = true;
+        advice.push(Advice {
+            mask,
+            kept: combos.faces(k),
+            value: keeps[k],
+        });
+    }
+
+    advice.sort_by(|a, b| {
+        b.value
+            .partial_cmp(&a.value)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    Ok(advice)
+}
+
+/// What a final hand is worth towards the goal, across every column.
+fn hand_value(card: &[ScoreCardData], hand: &Dice, goal: Goal) -> f64 {
+    card.iter()
+        .enumerate()
+        .flat_map(|(column, scorecard)| {
+            let bonus = if scorecard.is_joker(hand)
+                && scorecard
+                    .get_line_by_id(LineId::Dice5)
+                    .is_ok_and(|l| l.value.unwrap_or(0) > 0)
+            {
+                scorecard.rules.dice5_bonus
+            } else {
+                0
+            };
+
+            scorecard
+                .preview(hand)
+                .into_iter()
+                .map(move |(id, points)| match goal {
+                    Goal::Points => f64::from((points + bonus) * multiplier(column)),
+                    Goal::Line(target) if id == target && points > 0 => 1.0,
+                    Goal::Line(_) => 0.0,
+                })
+        })
+        .fold(0.0, f64::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::LineId as L;
+    use super::super::{get_new_scorecard_data, RuleSet};
+    use super::*;
+
+    /// A card with every line filled except `open`.
+    fn card_with_open(open: &[LineId]) -> Vec<ScoreCardData> {
+        let mut card = get_new_scorecard_data(RuleSet::default());
+        for line in card.line.iter_mut() {
+            if !open.contains(&line.id) {
+                line.value = Some(0);
+            }
+        }
+        vec![card]
+    }
+
+    fn hand(dice: Vec<DieFace>, rolls_left: i8) -> Dice {
+        let mut hand = Dice::roll_fake(dice);
+        hand.rolls_left = rolls_left;
+        hand
+    }
+
+    #[test]
+    fn rerolls_the_odd_die_for_chance() {
+        let card = card_with_open(&[L::Chance]);
+        let advice = advise(&card, &hand(vec![6, 6, 1, 6, 6], 1), Goal::Points).unwrap();
+
+        assert_eq!(advice[0].mask, vec![false, false, true, false, false]);
+        assert_eq!(advice[0].kept, vec![6, 6, 6, 6]);
+        assert!((advice[0].value - 27.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn chases_the_large_straight() {
+        let card = card_with_open(&[L::LargeStraight, L::Chance]);
+        let advice = advise(
+            &card,
+            &hand(vec![1, 2, 3, 4, 6], 1),
+            Goal::Line(L::LargeStraight),
+        )
+        .unwrap();
+
+        assert!((advice[0].value - 1.0 / 6.0).abs() < 1e-9);
+        assert_eq!(advice[0].kept.len(), 4);
+        let keep_three = advice.iter().find(|a| a.kept == vec![2, 3, 4]).unwrap();
+        assert!((keep_three.value - 1.0 / 9.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn lists_each_keep_once() {
+        let card = card_with_open(&[L::Chance]);
+        let advice = advise(&card, &hand(vec![3, 3, 3, 3, 3], 2), Goal::Points).unwrap();
+        assert_eq!(advice.len(), 6);
+
+        let advice = advise(&card, &hand(vec![1, 2, 3, 4, 5], 2), Goal::Points).unwrap();
+        assert_eq!(advice.len(), 32);
+    }
+
+    #[test]
+    fn nothing_to_advise_without_rolls() {
+        let card = card_with_open(&[L::Chance]);
+        assert_eq!(
+            advise(&card, &hand(vec![1, 2, 3, 4, 5], 0), Goal::Points),
+            Ok(vec![])
+        );
+    }
+
+    #[test]
+    fn refuses_hands_that_are_not_dice() {
+        let card = card_with_open(&[L::Chance]);
+        assert_eq!(
+            advise(&card, &hand(vec![1, 2, 3], 2), Goal::Points),
+            Err(GameError::DiceCount)
+        );
+        assert_eq!(
+            advise(&card, &hand(vec![1, 2, 3, 4, 9], 2), Goal::Points),
+            Err(GameError::InvalidDieFace)
+        );
+    }
+}
@@ -1,17 +1,17 @@
 use super::hand::{Dice, DieFace};
+use super::rules::{RuleSet, Scoring};
+use super::LineId;
 
-const VALUE_SMALL_STRAIGHT: i16 = 30;
-const VALUE_LARGE_STRAIGHT: i16 = 40;
-const VALUE_FULL_HOUSE: i16 = 25;
-const VALUE_DICE5: i16 = 50;
+pub type CalcFn = fn(hand: &Dice, rules: &RuleSet, special_dice5: bool) -> i16;
 
 fn sum_faces(hand: &Dice, face: DieFace) -> i16 {
     i16::from(hand.dice.iter().filter(|x| **x == face).sum::<DieFace>())
 }
 
+/// How many dice show each face, indexed from face 1.
 fn sort_faces(hand: &Dice) -> Vec<usize> {
-    (1..Dice::NUMBER_OF_DICE + 2)
-        .map(|face| hand.dice.iter().filter(|f| **f == face as DieFace).count())
+    (1..=Dice::NUMBER_OF_FACES)
+        .map(|face| hand.dice.iter().filter(|&&f| f == face).count())
         .collect()
 }
 
@@ -29,36 +29,37 @@ fn sum_all_dice(hand: &Dice) -> i16 {
     hand.dice.iter().map(|&f| i16::from(f)).sum()
 }
 
+/// Every die shows the same face.
 pub fn is_dice5(hand: &Dice) -> bool {
     let faces_count = sort_faces(hand);
-    faces_count.iter().any(|&f| f >= 5)
+    !hand.dice.is_empty() && faces_count.contains(&hand.dice.len())
 }
 
-pub fn calc_ace(hand: &Dice, _special_dice5: bool) -> i16 {
+pub fn calc_ace(hand: &Dice, _rules: &RuleSet, _special_dice5: bool) -> i16 {
     sum_faces(hand, 1)
 }
 
-pub fn calc_two(hand: &Dice, _special_dice5: bool) -> i16 {
+pub fn calc_two(hand: &Dice, _rules: &RuleSet, _special_dice5: bool) -> i16 {
     sum_faces(hand, 2)
 }
 
-pub fn calc_three(hand: &Dice, _special_dice5: bool) -> i16 {
+pub fn calc_three(hand: &Dice, _rules: &RuleSet, _special_dice5: bool) -> i16 {
     sum_faces(hand, 3)
 }
 
-pub fn calc_four(hand: &Dice, _special_dice5: bool) -> i16 {
+pub fn calc_four(hand: &Dice, _rules: &RuleSet, _special_dice5: bool) -> i16 {
     sum_faces(hand, 4)
 }
 
-pub fn calc_five(hand: &Dice, _special_dice5: bool) -> i16 {
+pub fn calc_five(hand: &Dice, _rules: &RuleSet, _special_dice5: bool) -> i16 {
     sum_faces(hand, 5)
 }
 
-pub fn calc_six(hand: &Dice, _special_dice5: bool) -> i16 {
+pub fn calc_six(hand: &Dice, _rules: &RuleSet, _special_dice5: bool) -> i16 {
     sum_faces(hand, 6)
 }
 
-pub fn calc_3k(hand: &Dice, _special_dice5: bool) -> i16 {
+pub fn calc_3k(hand: &Dice, _rules: &RuleSet, _special_dice5: bool) -> i16 {
     let faces_count = sort_faces(hand);
     let piles_of_at_least_three = faces_count.iter().any(|&f| f >= 3);
     if piles_of_at_least_three {
@@ -68,7 +69,7 @@ pub fn calc_3k(hand: &Dice, _special_dice5: bool) -> i16 {
     }
 }
 
-pub fn calc_4k(hand: &Dice, _special_dice5: bool) -> i16 {
+pub fn calc_4k(hand: &Dice, _rules: &RuleSet, _special_dice5: bool) -> i16 {
     let faces_count = sort_faces(hand);
     let piles_of_at_least_four = faces_count.iter().any(|&f| f >= 4);
     if piles_of_at_least_four {
@@ -78,78 +79,246 @@ pub fn calc_4k(hand: &Dice, _special_dice5: bool) -> i16 {
     }
 }
 
-pub fn calc_ss(hand: &Dice, special_dice5: bool) -> i16 {
-    if special_dice5 {
-        if is_dice5(hand) {
-            return VALUE_SMALL_STRAIGHT;
-        } else {
-            panic!("Illegal call");
-        }
+pub fn calc_ss(hand: &Dice, rules: &RuleSet, special_dice5: bool) -> i16 {
+    if special_dice5 && is_dice5(hand) {
+        return rules.small_straight;
     }
 
     let str = hand_to_string(hand);
 
     if str.contains("++++") {
-        VALUE_SMALL_STRAIGHT
+        rules.small_straight
     } else {
         0
     }
 }
 
-pub fn calc_ls(hand: &Dice, special_dice5: bool) -> i16 {
-    if special_dice5 {
-        if is_dice5(hand) {
-            return VALUE_LARGE_STRAIGHT;
-        } else {
-            panic!("Illegal call");
-        }
+pub fn calc_ls(hand: &Dice, rules: &RuleSet, special_dice5: bool) -> i16 {
+    if special_dice5 && is_dice5(hand) {
+        return rules.large_straight;
     }
 
     let str = hand_to_string(hand);
 
     if str.contains("+++++") {
-        VALUE_LARGE_STRAIGHT
+        rules.large_straight
     } else {
         0
     }
 }
 
-pub fn calc_dice5(hand: &Dice, _special_dice5: bool) -> i16 {
+pub fn calc_dice5(hand: &Dice, rules: &RuleSet, _special_dice5: bool) -> i16 {
     if is_dice5(hand) {
-        VALUE_DICE5
+        rules.dice5
     } else {
         0
     }
 }
 
-pub fn calc_fh(hand: &Dice, special_dice5: bool) -> i16 {
-    if special_dice5 {
-        if is_dice5(hand) {
-            return VALUE_FULL_HOUSE;
-        } else {
-            panic!("Illegal call");
-        }
+pub fn calc_fh(hand: &Dice, rules: &RuleSet, special_dice5: bool) -> i16 {
+    if special_dice5 && is_dice5(hand) {
+        return rules.full_house;
     }
 
     let faces_count = sort_faces(hand);
-    let piles_of_at_exactly_3 = faces_count.iter().any(|&f| f == 3);
-    let piles_of_at_exactly_2 = faces_count.iter().any(|&f| f == 2);
+    let piles_of_at_exactly_3 = faces_count.contains(&3);
+    let piles_of_at_exactly_2 = faces_count.contains(&2);
 
     if piles_of_at_exactly_3 && piles_of_at_exactly_2 {
-        VALUE_FULL_HOUSE
+        rules.full_house
     } else {
         0
     }
 }
 
-pub fn calc_chance(hand: &Dice, _special_dice5: bool) -> i16 {
+pub fn calc_chance(hand: &Dice, _rules: &RuleSet, _special_dice5: bool) -> i16 {
     sum_all_dice(hand)
 }
 
+/// Faces showing on at least `count` dice, highest first.
+fn faces_with_at_least(hand: &Dice, count: usize) -> Vec<DieFace> {
+    let faces_count = sort_faces(hand);
+    (0..faces_count.len())
+        .rev()
+        .filter(|&i| faces_count[i] >= count)
+        .map(|i| (i + 1) as DieFace)
+        .collect()
+}
+
+fn sum_of_kind(hand: &Dice, count: usize) -> i16 {
+    match faces_with_at_least(hand, count).first() {
+        Some(&face) => i16::from(face) * count as i16,
+        None => 0,
+    }
+}
+
+pub fn calc_pair(hand: &Dice, _rules: &RuleSet, _special_dice5: bool) -> i16 {
+    sum_of_kind(hand, 2)
+}
+
+pub fn calc_two_pairs(hand: &Dice, _rules: &RuleSet, _special_dice5: bool) -> i16 {
+    let pairs = faces_with_at_least(hand, 2);
+    if pairs.len() >= 2 {
+        pairs[..2].iter().map(|&face| i16::from(face) * 2).sum()
+    } else {
+        0
+    }
+}
+
+pub fn calc_3k_matched(hand: &Dice, _rules: &RuleSet, _special_dice5: bool) -> i16 {
+    sum_of_kind(hand, 3)
+}
+
+pub fn calc_4k_matched(hand: &Dice, _rules: &RuleSet, _special_dice5: bool) -> i16 {
+    sum_of_kind(hand, 4)
+}
+
+pub fn calc_ss_fixed(hand: &Dice, rules: &RuleSet, _special_dice5: bool) -> i16 {
+    if hand_to_string(hand).starts_with("+++++") {
+        rules.small_straight
+    } else {
+        0
+    }
+}
+
+pub fn calc_ls_fixed(hand: &Dice, rules: &RuleSet, _special_dice5: bool) -> i16 {
+    if hand_to_string(hand).ends_with("+++++") {
+        rules.large_straight
+    } else {
+        0
+    }
+}
+
+/// Best score from a pile of `big` dice plus a different pile of `small`.
+fn sum_of_piles(hand: &Dice, big: usize, small: usize) -> i16 {
+    let mut best = 0;
+    for &high in &faces_with_at_least(hand, big) {
+        for &low in &faces_with_at_least(hand, small) {
+            if low != high {
+                let score = i16::from(high) * big as i16 + i16::from(low) * small as i16;
+                best = best.max(score);
+            }
+        }
+    }
+    best
+}
+
+pub fn calc_fh_sum(hand: &Dice, _rules: &RuleSet, _special_dice5: bool) -> i16 {
+    sum_of_piles(hand, 3, 2)
+}
+
+pub fn calc_three_pairs(hand: &Dice, _rules: &RuleSet, _special_dice5: bool) -> i16 {
+    let pairs = faces_with_at_least(hand, 2);
+    if pairs.len() >= 3 {
+        pairs[..3].iter().map(|&face| i16::from(face) * 2).sum()
+    } else {
+        0
+    }
+}
+
+pub fn calc_castle(hand: &Dice, _rules: &RuleSet, _special_dice5: bool) -> i16 {
+    sum_of_piles(hand, 3, 3)
+}
+
+pub fn calc_tower(hand: &Dice, _rules: &RuleSet, _special_dice5: bool) -> i16 {
+    sum_of_piles(hand, 4, 2)
+}
+
+pub fn calc_full_straight(hand: &Dice, rules: &RuleSet, _special_dice5: bool) -> i16 {
+    if hand_to_string(hand) == "++++++" {
+        rules.full_straight
+    } else {
+        0
+    }
+}
+
+fn served(hand: &Dice, rules: &RuleSet) -> i16 {
+    if hand.roll_number == 1 {
+        rules.served_bonus
+    } else {
+        0
+    }
+}
+
+pub fn calc_straight_served(hand: &Dice, rules: &RuleSet, _special_dice5: bool) -> i16 {
+    if hand_to_string(hand).contains("+++++") {
+        rules.large_straight + served(hand, rules)
+    } else {
+        0
+    }
+}
+
+pub fn calc_fh_served(hand: &Dice, rules: &RuleSet, _special_dice5: bool) -> i16 {
+    let faces_count = sort_faces(hand);
+    if faces_count.contains(&3) && faces_count.contains(&2) {
+        rules.full_house + served(hand, rules)
+    } else {
+        0
+    }
+}
+
+pub fn calc_4k_served(hand: &Dice, rules: &RuleSet, _special_dice5: bool) -> i16 {
+    let faces_count = sort_faces(hand);
+    if faces_count.iter().any(|&f| f >= 4) {
+        rules.four_kind + served(hand, rules)
+    } else {
+        0
+    }
+}
+
+pub fn calc_double_dice5(hand: &Dice, rules: &RuleSet, _special_dice5: bool) -> i16 {
+    if is_dice5(hand) {
+        rules.double_dice5
+    } else {
+        0
+    }
+}
+
+fn calc_nothing(_hand: &Dice, _rules: &RuleSet, _special_dice5: bool) -> i16 {
+    0
+}
+
+/// Looks up how a line is scored under the given family of rules.
+pub fn get_calc(zid: LineId, scoring: Scoring) -> CalcFn {
+    match (scoring, zid) {
+        (_, LineId::Ace) => calc_ace,
+        (_, LineId::Two) => calc_two,
+        (_, LineId::Three) => calc_three,
+        (_, LineId::Four) => calc_four,
+        (_, LineId::Five) => calc_five,
+        (_, LineId::Six) => calc_six,
+        (_, LineId::OnePair) => calc_pair,
+        (_, LineId::TwoPairs) => calc_two_pairs,
+        (Scoring::Dice5, LineId::ThreeKind) => calc_3k,
+        (Scoring::Dice5, LineId::FourKind) => calc_4k,
+        (Scoring::Dice5, LineId::SmallStraight) => calc_ss,
+        (Scoring::Dice5, LineId::LargeStraight) => calc_ls,
+        (Scoring::Dice5, LineId::FullHouse) => calc_fh,
+        (Scoring::Generala, LineId::LargeStraight) => calc_straight_served,
+        (Scoring::Generala, LineId::FullHouse) => calc_fh_served,
+        (Scoring::Generala, LineId::FourKind) => calc_4k_served,
+        (_, LineId::ThreeKind) => calc_3k_matched,
+        (_, LineId::FourKind) => calc_4k_matched,
+        (_, LineId::SmallStraight) => calc_ss_fixed,
+        (_, LineId::LargeStraight) => calc_ls_fixed,
+        (_, LineId::FullHouse) => calc_fh_sum,
+        (_, LineId::ThreePairs) => calc_three_pairs,
+        (_, LineId::Castle) => calc_castle,
+        (_, LineId::Tower) => calc_tower,
+        (_, LineId::FullStraight) => calc_full_straight,
+        (_, LineId::Chance) => calc_chance,
+        (_, LineId::Dice5) => calc_dice5,
+        (_, LineId::DoubleDice5) => calc_double_dice5,
+        _ => calc_nothing,
+    }
+}
+
 #[cfg(test)]
+#[allow(clippy::identity_op, clippy::erasing_op)]
 mod tests {
     use super::super::get_new_scorecard_data;
     use super::super::LineId as L;
+    use super::super::RuleSet;
     use super::*;
 
     #[test]
@@ -157,8 +326,8 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![1, 1, 1, 1, 1];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::Ace).calc)(&hand, false);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score = get_calc(L::Ace, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
         assert_eq!(score, 1 * 5);
     }
 
@@ -167,8 +336,8 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![1, 1, 2, 3, 2];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::Ace).calc)(&hand, false);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score = get_calc(L::Ace, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
         assert_eq!(score, 1 * 2);
     }
 
@@ -177,8 +346,8 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![2, 2, 2, 2, 2];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::Two).calc)(&hand, false);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score = get_calc(L::Two, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
         assert_eq!(score, 2 * 5);
     }
 
@@ -187,8 +356,8 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![2, 2, 3, 4, 5];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::Two).calc)(&hand, false);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score = get_calc(L::Two, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
         assert_eq!(score, 2 * 2);
     }
 
@@ -197,8 +366,8 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![3, 3, 3, 3, 3];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::Three).calc)(&hand, false);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score = get_calc(L::Three, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
         assert_eq!(score, 3 * 5);
     }
 
@@ -207,8 +376,8 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![3, 3, 3, 3, 1];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::Three).calc)(&hand, false);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score = get_calc(L::Three, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
         assert_eq!(score, 3 * 4);
     }
 
@@ -217,8 +386,8 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![4, 4, 4, 4, 4];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::Four).calc)(&hand, false);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score = get_calc(L::Four, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
         assert_eq!(score, 4 * 5);
     }
 
@@ -227,8 +396,8 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![1, 2, 3, 5, 5];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::Four).calc)(&hand, false);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score = get_calc(L::Four, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
         assert_eq!(score, 4 * 0);
     }
 
@@ -237,8 +406,8 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![5, 5, 5, 5, 5];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::Five).calc)(&hand, false);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score = get_calc(L::Five, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
         assert_eq!(score, 5 * 5);
     }
 
@@ -247,8 +416,8 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![6, 6, 6, 6, 6];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::Six).calc)(&hand, false);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score = get_calc(L::Six, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
         assert_eq!(score, 6 * 5);
     }
 
@@ -257,8 +426,8 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![6, 6, 6, 1, 2];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::ThreeKind).calc)(&hand, false);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score = get_calc(L::ThreeKind, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
         assert_eq!(score, (6 * 3) + 1 + 2);
     }
 
@@ -267,8 +436,8 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![1, 1, 1, 1, 1];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::ThreeKind).calc)(&hand, false);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score = get_calc(L::ThreeKind, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
         assert_eq!(score, 5);
     }
 
@@ -277,8 +446,8 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![3, 3, 3, 3, 1];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::ThreeKind).calc)(&hand, false);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score = get_calc(L::ThreeKind, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
         assert_eq!(score, 3 * 4 + 1);
     }
 
@@ -287,8 +456,8 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![1, 1, 2, 2, 3];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::ThreeKind).calc)(&hand, false);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score = get_calc(L::ThreeKind, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
         assert_eq!(score, 0);
     }
 
@@ -297,8 +466,8 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![6, 6, 6, 6, 2];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::FourKind).calc)(&hand, false);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score = get_calc(L::FourKind, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
         assert_eq!(score, (6 * 4) + 2);
     }
 
@@ -307,8 +476,8 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![1, 1, 1, 1, 1];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::FourKind).calc)(&hand, false);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score = get_calc(L::FourKind, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
         assert_eq!(score, 5);
     }
 
@@ -317,8 +486,8 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![1, 1, 1, 2, 2];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::FourKind).calc)(&hand, false);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score = get_calc(L::FourKind, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
         assert_eq!(score, 0);
     }
 
@@ -327,9 +496,10 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![1, 4, 3, 2, 1];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::SmallStraight).calc)(&hand, false);
-        assert_eq!(score, VALUE_SMALL_STRAIGHT);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score =
+            get_calc(L::SmallStraight, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
+        assert_eq!(score, scorecard.rules.small_straight);
     }
 
     #[test]
@@ -337,9 +507,10 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![2, 4, 3, 2, 5];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::SmallStraight).calc)(&hand, false);
-        assert_eq!(score, VALUE_SMALL_STRAIGHT);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score =
+            get_calc(L::SmallStraight, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
+        assert_eq!(score, scorecard.rules.small_straight);
     }
 
     #[test]
@@ -347,9 +518,10 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![6, 3, 4, 6, 5];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::SmallStraight).calc)(&hand, false);
-        assert_eq!(score, VALUE_SMALL_STRAIGHT);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score =
+            get_calc(L::SmallStraight, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
+        assert_eq!(score, scorecard.rules.small_straight);
     }
 
     #[test]
@@ -357,9 +529,10 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![6, 3, 4, 2, 5];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::SmallStraight).calc)(&hand, false);
-        assert_eq!(score, VALUE_SMALL_STRAIGHT);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score =
+            get_calc(L::SmallStraight, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
+        assert_eq!(score, scorecard.rules.small_straight);
     }
 
     #[test]
@@ -367,8 +540,9 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![6, 3, 3, 2, 5];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::SmallStraight).calc)(&hand, false);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score =
+            get_calc(L::SmallStraight, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
         assert_eq!(score, 0);
     }
 
@@ -377,9 +551,10 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![3, 3, 3, 3, 3];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::SmallStraight).calc)(&hand, true);
-        assert_eq!(score, VALUE_SMALL_STRAIGHT);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score =
+            get_calc(L::SmallStraight, scorecard.rules.scoring)(&hand, &scorecard.rules, true);
+        assert_eq!(score, scorecard.rules.small_straight);
     }
 
     #[test]
@@ -387,9 +562,10 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![6, 3, 4, 2, 5];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::LargeStraight).calc)(&hand, false);
-        assert_eq!(score, VALUE_LARGE_STRAIGHT);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score =
+            get_calc(L::LargeStraight, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
+        assert_eq!(score, scorecard.rules.large_straight);
     }
 
     #[test]
@@ -397,8 +573,9 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![6, 2, 2, 2, 5];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::LargeStraight).calc)(&hand, false);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score =
+            get_calc(L::LargeStraight, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
         assert_eq!(score, 0);
     }
 
@@ -407,9 +584,10 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![1, 1, 1, 1, 1];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::LargeStraight).calc)(&hand, true);
-        assert_eq!(score, VALUE_LARGE_STRAIGHT);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score =
+            get_calc(L::LargeStraight, scorecard.rules.scoring)(&hand, &scorecard.rules, true);
+        assert_eq!(score, scorecard.rules.large_straight);
     }
 
     #[test]
@@ -417,9 +595,10 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![1, 3, 4, 2, 5];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::LargeStraight).calc)(&hand, false);
-        assert_eq!(score, VALUE_LARGE_STRAIGHT);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score =
+            get_calc(L::LargeStraight, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
+        assert_eq!(score, scorecard.rules.large_straight);
     }
 
     #[test]
@@ -427,8 +606,8 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![6, 3, 3, 2, 5];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::FullHouse).calc)(&hand, false);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score = get_calc(L::FullHouse, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
         assert_eq!(score, 0);
     }
 
@@ -437,9 +616,9 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![6, 3, 3, 6, 3];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::FullHouse).calc)(&hand, false);
-        assert_eq!(score, VALUE_FULL_HOUSE);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score = get_calc(L::FullHouse, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
+        assert_eq!(score, scorecard.rules.full_house);
     }
 
     #[test]
@@ -447,9 +626,9 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![2, 2, 2, 2, 2];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::FullHouse).calc)(&hand, true);
-        assert_eq!(score, VALUE_FULL_HOUSE);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score = get_calc(L::FullHouse, scorecard.rules.scoring)(&hand, &scorecard.rules, true);
+        assert_eq!(score, scorecard.rules.full_house);
     }
 
     #[test]
@@ -457,8 +636,8 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![1, 2, 3, 4, 5];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::Chance).calc)(&hand, false);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score = get_calc(L::Chance, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
         assert_eq!(score, 1 + 2 + 3 + 4 + 5);
     }
 
@@ -467,9 +646,9 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![1, 1, 1, 1, 1];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::Dice5).calc)(&hand, false);
-        assert_eq!(score, VALUE_DICE5);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score = get_calc(L::Dice5, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
+        assert_eq!(score, scorecard.rules.dice5);
     }
 
     #[test]
@@ -477,8 +656,8 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![2, 1, 1, 1, 1];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::Dice5).calc)(&hand, false);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score = get_calc(L::Dice5, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
         assert_eq!(score, 0);
     }
 
@@ -487,8 +666,247 @@ mod tests {
         let test_dice: Vec<DieFace> = vec![0, 0, 0, 0, 0];
         let hand = Dice::roll_fake(test_dice);
 
-        let scorecard = get_new_scorecard_data();
-        let score = (scorecard.get_line_by_id(L::Ace).calc)(&hand, false);
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score = get_calc(L::Ace, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
         assert_eq!(score, 0);
     }
+
+    #[test]
+    fn test_pair_highest_pair() {
+        let hand = Dice::roll_fake(vec![2, 2, 5, 5, 6]);
+
+        let scorecard = get_new_scorecard_data(RuleSet::yatzy());
+        let score = get_calc(L::OnePair, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
+        assert_eq!(score, 5 * 2);
+    }
+
+    #[test]
+    fn test_pair_no_pair() {
+        let hand = Dice::roll_fake(vec![1, 2, 3, 4, 6]);
+
+        let scorecard = get_new_scorecard_data(RuleSet::yatzy());
+        let score = get_calc(L::OnePair, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
+        assert_eq!(score, 0);
+    }
+
+    #[test]
+    fn test_two_pairs() {
+        let hand = Dice::roll_fake(vec![2, 2, 5, 5, 6]);
+
+        let scorecard = get_new_scorecard_data(RuleSet::yatzy());
+        let score = get_calc(L::TwoPairs, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
+        assert_eq!(score, 2 * 2 + 5 * 2);
+    }
+
+    #[test]
+    fn test_two_pairs_four_of_a_kind_is_not_two_pairs() {
+        let hand = Dice::roll_fake(vec![3, 3, 3, 3, 6]);
+
+        let scorecard = get_new_scorecard_data(RuleSet::yatzy());
+        let score = get_calc(L::TwoPairs, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
+        assert_eq!(score, 0);
+    }
+
+    #[test]
+    fn test_3k_matched_scores_only_matched_dice() {
+        let hand = Dice::roll_fake(vec![4, 4, 4, 4, 6]);
+
+        let scorecard = get_new_scorecard_data(RuleSet::yatzy());
+        let score = get_calc(L::ThreeKind, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
+        assert_eq!(score, 4 * 3);
+    }
+
+    #[test]
+    fn test_4k_matched_scores_only_matched_dice() {
+        let hand = Dice::roll_fake(vec![4, 4, 4, 4, 6]);
+
+        let scorecard = get_new_scorecard_data(RuleSet::yatzy());
+        let score = get_calc(L::FourKind, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
+        assert_eq!(score, 4 * 4);
+    }
+
+    #[test]
+    fn test_small_straight_fixed() {
+        let scorecard = get_new_scorecard_data(RuleSet::yatzy());
+        let calc = get_calc(L::SmallStraight, scorecard.rules.scoring);
+
+        let hand = Dice::roll_fake(vec![5, 3, 1, 2, 4]);
+        assert_eq!(calc(&hand, &scorecard.rules, false), 15);
+
+        let hand = Dice::roll_fake(vec![5, 3, 6, 2, 4]);
+        assert_eq!(calc(&hand, &scorecard.rules, false), 0);
+    }
+
+    #[test]
+    fn test_large_straight_fixed() {
+        let scorecard = get_new_scorecard_data(RuleSet::yatzy());
+        let calc = get_calc(L::LargeStraight, scorecard.rules.scoring);
+
+        let hand = Dice::roll_fake(vec![5, 3, 6, 2, 4]);
+        assert_eq!(calc(&hand, &scorecard.rules, false), 20);
+
+        let hand = Dice::roll_fake(vec![5, 3, 1, 2, 4]);
+        assert_eq!(calc(&hand, &scorecard.rules, false), 0);
+    }
+
+    #[test]
+    fn test_full_house_sum() {
+        let hand = Dice::roll_fake(vec![6, 3, 3, 6, 3]);
+
+        let scorecard = get_new_scorecard_data(RuleSet::yatzy());
+        let score = get_calc(L::FullHouse, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
+        assert_eq!(score, 6 * 2 + 3 * 3);
+    }
+
+    #[test]
+    fn test_full_house_sum_yatzy_is_not_full_house() {
+        let hand = Dice::roll_fake(vec![3, 3, 3, 3, 3]);
+
+        let scorecard = get_new_scorecard_data(RuleSet::yatzy());
+        let score = get_calc(L::FullHouse, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
+        assert_eq!(score, 0);
+    }
+
+    #[test]
+    fn test_maxi_three_pairs() {
+        let hand = Dice::roll_fake(vec![1, 1, 4, 4, 6, 6]);
+
+        let scorecard = get_new_scorecard_data(RuleSet::maxi());
+        let score =
+            get_calc(L::ThreePairs, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
+        assert_eq!(score, 2 + 8 + 12);
+    }
+
+    #[test]
+    fn test_maxi_castle() {
+        let hand = Dice::roll_fake(vec![2, 5, 2, 5, 2, 5]);
+
+        let scorecard = get_new_scorecard_data(RuleSet::maxi());
+        let score = get_calc(L::Castle, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
+        assert_eq!(score, 6 + 15);
+    }
+
+    #[test]
+    fn test_maxi_tower() {
+        let hand = Dice::roll_fake(vec![3, 3, 3, 3, 6, 6]);
+
+        let scorecard = get_new_scorecard_data(RuleSet::maxi());
+        let score = get_calc(L::Tower, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
+        assert_eq!(score, 12 + 12);
+    }
+
+    #[test]
+    fn test_maxi_full_straight() {
+        let hand = Dice::roll_fake(vec![6, 5, 4, 3, 2, 1]);
+
+        let scorecard = get_new_scorecard_data(RuleSet::maxi());
+        let score =
+            get_calc(L::FullStraight, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
+        assert_eq!(score, 21);
+    }
+
+    #[test]
+    fn test_maxi_small_straight_with_extra_die() {
+        let hand = Dice::roll_fake(vec![1, 2, 3, 4, 5, 5]);
+
+        let scorecard = get_new_scorecard_data(RuleSet::maxi());
+        let score =
+            get_calc(L::SmallStraight, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
+        assert_eq!(score, 15);
+    }
+
+    #[test]
+    fn test_maxi_full_house_uses_best_piles() {
+        let hand = Dice::roll_fake(vec![2, 2, 2, 6, 6, 6]);
+
+        let scorecard = get_new_scorecard_data(RuleSet::maxi());
+        let score = get_calc(L::FullHouse, scorecard.rules.scoring)(&hand, &scorecard.rules, false);
+        assert_eq!(score, 6 * 3 + 2 * 2);
+    }
+
+    #[test]
+    fn test_maxi_yatzy_needs_all_six_dice() {
+        let scorecard = get_new_scorecard_data(RuleSet::maxi());
+        let calc = get_calc(L::Dice5, scorecard.rules.scoring);
+
+        let hand = Dice::roll_fake(vec![4, 4, 4, 4, 4, 4]);
+        assert_eq!(calc(&hand, &scorecard.rules, false), 100);
+
+        let hand = Dice::roll_fake(vec![4, 4, 4, 4, 4, 1]);
+        assert_eq!(calc(&hand, &scorecard.rules, false), 0);
+    }
+
+    #[test]
+    fn test_yacht_straights_and_four_kind() {
+        let scorecard = get_new_scorecard_data(RuleSet::yacht());
+        let rules = &scorecard.rules;
+
+        let little = Dice::roll_fake(vec![1, 2, 3, 4, 5]);
+        let big = Dice::roll_fake(vec![2, 3, 4, 5, 6]);
+        let four = Dice::roll_fake(vec![5, 5, 5, 5, 2]);
+
+        assert_eq!(
+            get_calc(L::SmallStraight, scorecard.rules.scoring)(&little, rules, false),
+            30
+        );
+        assert_eq!(
+            get_calc(L::SmallStraight, scorecard.rules.scoring)(&big, rules, false),
+            0
+        );
+        assert_eq!(
+            get_calc(L::LargeStraight, scorecard.rules.scoring)(&big, rules, false),
+            30
+        );
+        assert_eq!(
+            get_calc(L::FourKind, scorecard.rules.scoring)(&four, rules, false),
+            20
+        );
+    }
+
+    #[test]
+    fn test_generala_served_bonus() {
+        let scorecard = get_new_scorecard_data(RuleSet::generala());
+        let calc = get_calc(L::FullHouse, scorecard.rules.scoring);
+
+        let mut hand = Dice::roll_fake(vec![2, 2, 3, 3, 3]);
+        assert_eq!(calc(&hand, &scorecard.rules, false), 35);
+
+        hand.roll_number = 2;
+        assert_eq!(calc(&hand, &scorecard.rules, false), 30);
+    }
+
+    #[test]
+    fn test_generala_escalera_either_straight() {
+        let scorecard = get_new_scorecard_data(RuleSet::generala());
+        let calc = get_calc(L::LargeStraight, scorecard.rules.scoring);
+
+        let mut hand = Dice::roll_fake(vec![1, 2, 3, 4, 5]);
+        hand.roll_number = 3;
+        assert_eq!(calc(&hand, &scorecard.rules, false), 20);
+
+        let hand = Dice::roll_fake(vec![6, 2, 3, 4, 5]);
+        assert_eq!(calc(&hand, &scorecard.rules, false), 25);
+    }
+
+    #[test]
+    fn test_generala_poker_and_double() {
+        let scorecard = get_new_scorecard_data(RuleSet::generala());
+        let hand = Dice::roll_fake(vec![6, 6, 6, 6, 6]);
+
+        let poker = get_calc(L::FourKind, scorecard.rules.scoring);
+        let double = get_calc(L::DoubleDice5, scorecard.rules.scoring);
+        assert_eq!(poker(&hand, &scorecard.rules, false), 45);
+        assert_eq!(double(&hand, &scorecard.rules, false), 100);
+    }
+
+    #[test]
+    fn test_joker_flag_without_dice5_scores_normally() {
+        let hand = Dice::roll_fake(vec![1, 2, 3, 4, 6]);
+
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let rules = &scorecard.rules;
+        assert_eq!(calc_ss(&hand, rules, true), 30);
+        assert_eq!(calc_ls(&hand, rules, true), 0);
+        assert_eq!(calc_fh(&hand, rules, true), 0);
+    }
 }
@@ -0,0 +1,302 @@
+use super::{Dice, DieFace, GameError};
+use std::ops::Range;
+
+const FACES: usize = Dice::NUMBER_OF_FACES as usize;
+
+/// How many dice show each face, indexed from face 1.
+pub type Counts = [u8; FACES];
+
+/// Every set of up to `number_of_dice` dice, ignoring their order, with the
+/// lookups the solver and the odds tables need to move between them.
+///
+/// A set of all `number_of_dice` dice is a hand; a smaller set is what is
+/// kept before a re-roll.
+#[derive(Clone, Debug)]
+pub struct Combos {
+    number_of_dice: usize,
+    sets: Vec<Counts>,
+    by_size: Vec<Range<usize>>,
+    lookup: Vec<u32>,
+    add: Vec<[u32; FACES]>,
+    sub: Vec<Vec<u32>>,
+}
+
+impl Combos {
+    pub fn new(number_of_dice: usize) -> Self {
+        let mut sets = vec![];
+        let mut by_size = vec![];
+        for size in 0..=number_of_dice {
+            let start = sets.len();
+            push_sets(&mut sets, &mut [0; FACES], 0, size);
+            by_size.push(start..sets.len());
+        }
+
+        let radix = number_of_dice + 1;
+        let mut lookup = vec![u32::MAX; radix.pow(FACES as u32)];
+        for (i, counts) in sets.iter().enumerate() {
+            lookup[encode(counts, radix)] = i as u32;
+        }
+
+        let mut combos = Self {
+            number_of_dice,
+            sets,
+            by_size,
+            lookup,
+            add: vec![],
+            sub: vec![],
+        };
+
+        combos.add = combos
+            .sets
+            .iter()
+            .map(|counts| {
+                let mut next = [u32::MAX; FACES];
+                if size_of(counts) < number_of_dice {
+                    for (face, slot) in next.iter_mut().enumerate() {
+                        let mut more = *counts;
+                        more[face] += 1;
+                        *slot = combos.index(&more) as u32;
+                    }
+                }
+                next
+            })
+            .collect();
+
+        combos.sub = combos
+            .sets
+            .iter()
+            .map(|counts| {
+                let mut subsets = vec![];
+                push_subsets(&combos, counts, &mut [0; FACES], 0, &mut subsets);
+                subsets
+            })
+            .collect();
+
+        combos
+    }
+
+    pub fn number_of_dice(&self) -> usize {
+        self.number_of_dice
+    }
+
+    /// The number of sets of every size together.
+    pub fn len(&self) -> usize {
+        self.sets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sets.is_empty()
+    }
+
+    pub fn counts(&self, index: usize) -> &Counts {
+        &self.sets[index]
+    }
+
+    /// The indexes of the sets holding exactly `size` dice.
+    pub fn of_size(&self, size: usize) -> Range<usize> {
+        self.by_size[size].clone()
+    }
+
+    /// The indexes of every full hand.
+    pub fn hands(&self) -> Range<usize> {
+        self.of_size(self.number_of_dice)
+    }
+
+    pub fn index(&self, counts: &Counts) -> usize {
+        self.lookup[encode(counts, self.number_of_dice + 1)] as usize
+    }
+
+    /// The set these faces make, refusing faces that are not on a die and
+    /// more dice than the sets hold.
+    pub fn index_of_faces(&self, faces: &[DieFace]) -> Result<usize, GameError> {
+        if faces.len() > self.number_of_dice {
+            return Err(GameError::TooManyDice);
+        }
+        if faces.iter().any(|f| !(1..=FACES as DieFace).contains(f)) {
+            return Err(GameError::InvalidDieFace);
+        }
+        Ok(self.index(&counts_of(faces)))
+    }
+
+    /// The set with one more die showing `face`, which must be 1 to 6.
+    pub(crate) fn with_die(&self, index: usize, face: DieFace) -> usize {
+        self.add[index][face as usize - 1] as usize
+    }
+
+    /// Every set that can be kept from this one, itself and the empty set
+    /// included.
+    pub fn subsets(&self, index: usize) -> &[u32] {
+        &self.sub[index]
+    }
+
+    /// The faces of a set, lowest first.
+    pub fn faces(&self, index: usize) -> Vec<DieFace> {
+        let mut faces = vec![];
+        for (face, &count) in self.sets[index].iter().enumerate() {
+            for _ in 0..count {
+                faces.push(face as DieFace + 1);
+            }
+        }
+        faces
+    }
+
+    /// In how many of the `6^n` ordered ways `n` dice can land they show
+    /// this set.
+    pub fn ways(&self, index: usize) -> u64 {
+        let counts = &self.sets[index];
+        let mut ways = factorial(size_of(counts));
+        for &count in counts.iter() {
+            ways /= factorial(count as usize);
+        }
+        ways
+    }
+
+    /// The chance of rolling exactly this set with as many dice as it holds.
+    pub fn probability(&self, index: usize) -> f64 {
+        let size = size_of(&self.sets[index]) as i32;
+        self.ways(index) as f64 / (FACES as f64).powi(size)
+    }
+
+    /// The expected value of keeping each set and rolling the rest, given
+    /// the value of every full hand.
+    pub fn keep_values(&self, hand_values: &[f64]) -> Vec<f64> {
+        let mut keeps = hand_values.to_vec();
+
+        for size in (0..self.number_of_dice).rev() {
+            for k in self.of_size(size) {
+                let total: f64 = (1..=FACES as DieFace)
+                    .map(|face| keeps[self.with_die(k, face)])
+                    .sum();
+                keeps[k] = total / FACES as f64;
+            }
+        }
+        keeps
+    }
+
+    /// The value of each full hand when the best set is kept from it.
+    pub fn best_of(&self, keeps: &[f64]) -> Vec<f64> {
+        let mut values = vec![0.0; self.len()];
+        for h in self.hands() {
+            values[h] = self
+                .subsets(h)
+                .iter()
+                .map(|&k| keeps[k as usize])
+                .fold(f64::MIN, f64::max);
+        }
+        values
+    }
+
+    /// The value of each kept set with `rolls` rolls left, playing on for
+    /// the best value, given the value of every full hand.
+    pub fn keep_values_with(&self, hand_values: Vec<f64>, rolls: usize) -> Vec<f64> {
+        let mut keeps = self.keep_values(&hand_values);
+        for _ in 1..rolls {
+            keeps = self.keep_values(&self.best_of(&keeps));
+        }
+        keeps
+    }
+}
+
+fn counts_of(faces: &[DieFace]) -> Counts {
+    let mut counts = [0; FACES];
+    for &face in faces {
+        counts[face as usize - 1] += 1;
+    }
+    counts
+}
+
+fn size_of(counts: &Counts) -> usize {
+    counts.iter().map(|&c| c as usize).sum()
+}
+
+fn factorial(n: usize) -> u64 {
+    (1..=n as u64).product()
+}
+
+fn encode(counts: &Counts, radix: usize) -> usize {
+    counts
+        .iter()
+        .rev()
+        .fold(0, |acc, &c| acc * radix + c as usize)
+}
+
+fn push_sets(sets: &mut Vec<Counts>, counts: &mut Counts, face: usize, left: usize) {
+    if face == FACES - 1 {
+        counts[face] = left as u8;
+        sets.push(*counts);
+        return;
+    }
+    for count in (0..=left).rev() {
+        counts[face] = count as u8;
+        push_sets(sets, counts, face + 1, left - count);
+    }
+}
+
+fn push_subsets(
+    combos: &Combos,
+    of: &Counts,
+    counts: &mut Counts,
+    face: usize,
+    out: &mut Vec<u32>,
+) {
+    if face == FACES {
+        out.push(combos.index(counts) as u32);
+        return;
+    }
+    for count in 0..=of[face] {
+        counts[face] = count;
+        push_subsets(combos, of, counts, face + 1, out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn five_dice_have_252_hands_and_462_keeps() {
+        let combos = Combos::new(5);
+        assert_eq!(combos.hands().len(), 252);
+        assert_eq!(combos.len(), 462);
+        assert_eq!(Combos::new(6).hands().len(), 462);
+    }
+
+    #[test]
+    fn hand_probabilities_add_up() {
+        let combos = Combos::new(5);
+        let total: u64 = combos.hands().map(|i| combos.ways(i)).sum();
+        assert_eq!(total, 6u64.pow(5));
+
+        let dice5 = combos.index_of_faces(&[4, 4, 4, 4, 4]).unwrap();
+        assert_eq!(combos.ways(dice5), 1);
+        let straight = combos.index_of_faces(&[5, 1, 3, 2, 4]).unwrap();
+        assert_eq!(combos.ways(straight), 120);
+    }
+
+    #[test]
+    fn sets_link_up() {
+        let combos = Combos::new(5);
+        let pair = combos.index_of_faces(&[3, 3]).unwrap();
+        let trips = combos.with_die(pair, 3);
+
+        assert_eq!(combos.faces(trips), vec![3, 3, 3]);
+        assert_eq!(combos.subsets(trips).len(), 4);
+        assert_eq!(
+            combos
+                .subsets(combos.index_of_faces(&[1, 2, 3, 4, 5]).unwrap())
+                .len(),
+            32
+        );
+    }
+
+    #[test]
+    fn refuses_sets_that_are_not_dice() {
+        let combos = Combos::new(5);
+        assert_eq!(
+            combos.index_of_faces(&[1, 2, 7]),
+            Err(GameError::InvalidDieFace)
+        );
+        assert_eq!(combos.index_of_faces(&[0]), Err(GameError::InvalidDieFace));
+        assert_eq!(combos.index_of_faces(&[1; 6]), Err(GameError::TooManyDice));
+    }
+}
@@ -0,0 +1,377 @@
+//! Bots written in any language, run as child processes and spoken to over
+//! their stdin and stdout, in the spirit of UCI for chess.
+//!
+//! ```text
+//! > dice5
+//! < id name first-open
+//! < ready
+//! > position rules modern order free card 1=3 2=- 3=- ... c=- d=- dice 3 4 4 6 1 rolls 2
+//! > go
+//! < keep 01100
+//! > position rules modern order free card 1=3 2=- 3=- ... dice 2 4 4 1 5 rolls 1
+//! > go
+//! < score 4
+//! > quit
+//! ```
+//!
+//! `position` lists each column of the card after `card`, every line by
+//! its short code from `text::get_short_name` with its points or `-` while
+//! open, then the dice and the re-rolls left.  The bot answers `go` with
+//! `keep` and a `0` or `1` for each die, `1` to keep it, or with `score` and
+//! a short code.  Triple games add the column, counting from 1: `score fh 2`.
+//!
+//! Before `ready` the bot may send anything; only `id name` is read.  While
+//! thinking it may send `info` lines, which are ignored.  A bot that takes
+//! longer than its move time to answer `go`, ten seconds unless set, has
+//! failed.
+
+use super::notation::order_name;
+use super::strategy::{Decision, Greedy, Strategy};
+use super::{Action, Game};
+use crate::text;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const DEFAULT_MOVE_TIME: Duration = Duration::from_secs(10);
+
+/// How many lines from the bot may wait to be read.
+const LINES_WAITING: usize = 64;
+
+pub struct External {
+    name: String,
+    child: Child,
+    stdin: ChildStdin,
+    /// The bot's output, line by line, read on a thread of its own so a
+    /// silent bot can be timed out.
+    lines: Receiver<io::Result<String>>,
+    move_time: Duration,
+    error: Option<String>,
+}
+
+impl External {
+    /// Starts the bot and waits for it to say `ready`.
+    pub fn spawn(mut command: Command) -> io::Result<Self> {
+        let name = command.get_program().to_string_lossy().into_owned();
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("piped stdin");
+        let stdout = BufReader::new(child.stdout.take().expect("piped stdout"));
+
+        // Bounded, so a bot that never stops talking waits for us to read.
+        let (sender, lines) = mpsc::sync_channel(LINES_WAITING);
+        thread::spawn(move || {
+            for line in stdout.lines() {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut bot = Self {
+            name,
+            child,
+            stdin,
+            lines,
+            move_time: DEFAULT_MOVE_TIME,
+            error: None,
+        };
+        bot.send("dice5")?;
+        let deadline = Instant::now() + bot.move_time;
+        loop {
+            let line = bot.read_line(deadline)?;
+            let words: Vec<_> = line.split_whitespace().collect();
+            match words.as_slice() {
+                ["ready"] => return Ok(bot),
+                ["id", "name", name @ ..] if !name.is_empty() => bot.name = name.join(" "),
+                _ => {}
+            }
+        }
+    }
+
+    /// Starts a command line such as `python3 bot.py`, split on whitespace.
+    pub fn from_command_line(line: &str) -> io::Result<Self> {
+        let mut words = line.split_whitespace();
+        let program = words
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no command to run"))?;
+        let mut command = Command::new(program);
+        command.args(words);
+        Self::spawn(command)
+    }
+
+    /// How long the bot may think about each `go`.
+    pub fn set_move_time(&mut self, move_time: Duration) {
+        self.move_time = move_time;
+    }
+
+    fn send(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.stdin, "{}", line)?;
+        self.stdin.flush()
+    }
+
+    fn timed_out(&self) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!(
+                "the bot did not answer within {} seconds",
+                self.move_time.as_secs_f64()
+            ),
+        )
+    }
+
+    /// The next line, unless `deadline` has passed.  Checked before every
+    /// line, since a bot that keeps talking always has one waiting.
+    fn read_line(&mut self, deadline: Instant) -> io::Result<String> {
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(self.timed_out());
+        }
+        match self.lines.recv_timeout(deadline - now) {
+            Ok(line) => line,
+            Err(RecvTimeoutError::Timeout) => Err(self.timed_out()),
+            Err(RecvTimeoutError::Disconnected) => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the bot has exited",
+            )),
+        }
+    }
+
+    fn ask(&mut self, game: &Game) -> Result<Decision, String> {
+        let asked = self.send(&position(game)).and_then(|()| self.send("go"));
+        asked.map_err(|err| err.to_string())?;
+
+        let deadline = Instant::now() + self.move_time;
+        loop {
+            let line = self.read_line(deadline).map_err(|err| err.to_string())?;
+            match line.split_whitespace().next() {
+                None | Some("info") => {}
+                Some(_) => return decision(&line, game),
+            }
+        }
+    }
+}
+
+impl Strategy for External {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn decide(&mut self, game: &Game) -> Decision {
+        if self.error.is_none() {
+            match self.ask(game) {
+                Ok(decision) => return decision,
+                Err(err) => self.error = Some(err),
+            }
+        }
+        Greedy.decide(game)
+    }
+
+    /// The first thing the bot got wrong.  From then on it is not asked
+    /// again, and `Greedy` plays in its place.
+    fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+}
+
+impl Drop for External {
+    fn drop(&mut self) {
+        self.send("quit").ok();
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+}
+
+/// The `position` line for the game as it stands.
+pub fn position(game: &Game) -> String {
+    let rules = game.rules();
+    let mut words = vec![
+        "position".to_string(),
+        "rules".to_string(),
+        rules.name.clone(),
+        "order".to_string(),
+        order_name(rules.order).to_string(),
+    ];
+
+    for column in game.card() {
+        words.push("card".to_string());
+        for line in &column.line {
+            let value = line.value.map_or("-".to_string(), |v| v.to_string());
+            words.push(format!("{}={}", text::get_short_name(line.id), value));
+        }
+    }
+
+    if let Some(hand) = game.dice() {
+        words.push("dice".to_string());
+        words.extend(hand.dice.iter().map(|face| face.to_string()));
+        words.push("rolls".to_string());
+        words.push(hand.rolls_left.to_string());
+    }
+    words.join(" ")
+}
+
+/// Reads the bot's answer, refusing anything the rules would not allow.
+fn decision(reply: &str, game: &Game) -> Result<Decision, String> {
+    let reply = reply.trim();
+    let words: Vec<_> = reply.split_whitespace().collect();
+    let legal = game.legal_actions();
+
+    match words.as_slice() {
+        ["keep", mask] => {
+            let dice = game.dice().map_or(0, |hand| hand.dice.len());
+            if mask.len() != dice || !mask.chars().all(|c| c == '0' || c == '1') {
+                return Err(format!(
+                    "'{}' needs a 0 or 1 for each of the {} dice",
+                    reply, dice
+                ));
+            }
+            if !legal.contains(&Action::Roll) {
+                return Err(format!("'{}' with no rolls left", reply));
+            }
+            Ok(Decision::Roll(mask.chars().map(|c| c == '0').collect()))
+        }
+        ["score", code, column @ ..] if column.len() <= 1 => {
+            let line = text::get_id_by_short_name(code)
+                .ok_or_else(|| format!("'{}' is not a line", code))?;
+            let column = match column.first() {
+                None => 0,
+                Some(c) => match c.parse::<usize>() {
+                    Ok(c) if c >= 1 => c - 1,
+                    _ => return Err(format!("'{}' is not a column", c)),
+                },
+            };
+            if !legal.contains(&Action::Score { column, line }) {
+                return Err(format!("'{}' is not allowed here", reply));
+            }
+            Ok(Decision::Score { column, line })
+        }
+        _ => Err(format!("expected 'keep' or 'score', not '{}'", reply)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::strategy::play_game;
+    use super::super::{Event, LineId, RuleSet, SeededRoller};
+    use super::*;
+
+    /// Scores the first open line every turn.
+    const FIRST_OPEN: &str = r#"
+        while read -r command rest; do
+            case "$command" in
+                dice5) echo "id name first open"; echo ready ;;
+                position)
+                    open=""
+                    for word in $rest; do
+                        case "$word" in *=-) open=${open:-${word%=-}} ;; esac
+                    done ;;
+                go) echo "info thinking"; echo "score $open" ;;
+                quit) exit ;;
+            esac
+        done
+    "#;
+
+    fn shell(script: &str) -> External {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(script);
+        External::spawn(command).unwrap()
+    }
+
+    fn game() -> Game {
+        let mut game = Game::new(RuleSet::default());
+        game.roll(&[]).unwrap();
+        game.cheat(vec![3, 4, 4, 6, 1]).unwrap();
+        game
+    }
+
+    #[test]
+    fn writes_the_position() {
+        let position = position(&game());
+        assert!(position.starts_with("position rules modern order free card 1=- 2=- "));
+        assert!(position.ends_with(" d=- dice 3 4 4 6 1 rolls 2"));
+    }
+
+    #[test]
+    fn reads_decisions() {
+        let game = game();
+        assert_eq!(
+            decision("keep 01100\n", &game),
+            Ok(Decision::Roll(vec![true, false, false, true, true]))
+        );
+        assert_eq!(
+            decision("score fh", &game),
+            Ok(Decision::Score {
+                column: 0,
+                line: LineId::FullHouse
+            })
+        );
+        assert!(decision("keep 011", &game).is_err());
+        assert!(decision("score fh 2", &game).is_err());
+        assert!(decision("score xx", &game).is_err());
+        assert!(decision("pass", &game).is_err());
+    }
+
+    #[test]
+    fn plays_a_game_through_a_process() {
+        let mut bot = shell(FIRST_OPEN);
+        assert_eq!(bot.name(), "first open");
+
+        let mut game = Game::new(RuleSet::default());
+        game.set_roller(Box::new(SeededRoller::new(4)));
+        play_game(&mut game, &mut bot).unwrap();
+
+        assert_eq!(bot.error(), None);
+        let played: Vec<LineId> = game
+            .log()
+            .iter()
+            .filter_map(|event| match event {
+                Event::Score { line, .. } => Some(*line),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(played, RuleSet::default().lines());
+    }
+
+    #[test]
+    fn a_broken_bot_is_replaced_by_greedy() {
+        let mut bot =
+            shell("echo ready; read -r line; read -r line; read -r line; echo hello; cat");
+        let mut game = Game::new(RuleSet::default());
+        game.set_roller(Box::new(SeededRoller::new(4)));
+
+        assert!(play_game(&mut game, &mut bot).is_ok());
+        assert_eq!(bot.error(), Some("expected 'keep' or 'score', not 'hello'"));
+    }
+
+    #[test]
+    fn a_bot_that_only_chatters_is_timed_out() {
+        let mut bot = shell("echo ready; yes info");
+        bot.set_move_time(Duration::from_millis(200));
+        let mut game = Game::new(RuleSet::default());
+        game.set_roller(Box::new(SeededRoller::new(4)));
+
+        assert!(play_game(&mut game, &mut bot).is_ok());
+        assert_eq!(
+            bot.error(),
+            Some("the bot did not answer within 0.2 seconds")
+        );
+    }
+
+    #[test]
+    fn a_bot_that_does_not_answer_is_timed_out() {
+        let mut bot = shell("echo ready; cat > /dev/null");
+        bot.set_move_time(Duration::from_millis(200));
+        let mut game = Game::new(RuleSet::default());
+        game.set_roller(Box::new(SeededRoller::new(4)));
+
+        assert!(play_game(&mut game, &mut bot).is_ok());
+        assert_eq!(
+            bot.error(),
+            Some("the bot did not answer within 0.2 seconds")
+        );
+    }
+}
@@ -0,0 +1,537 @@
+use super::triple;
+use super::{
+    get_new_scorecard_data, Dice, DiceRoller, DieFace, Event, GameError, LineId, OsRoller, RuleSet,
+    ScoreCardData, ScriptedRoller,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Phase {
+    /// A new turn has started and the dice have not been rolled yet.
+    AwaitingFirstRoll,
+    /// The dice are showing and may be re-rolled or scored.
+    Rolling,
+    /// No rolls are left; the hand has to be scored.
+    MustScore,
+    GameOver,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Action {
+    Roll,
+    Score { column: usize, line: LineId },
+}
+
+/// One player's game: the card, the dice on the table and the turn phase.
+///
+/// Every front end drives the same state machine, so the rules are checked
+/// here rather than in the user interface.  The whole state serializes, so
+/// a game can be saved, sent over the network or replayed.  The roller is
+/// not part of the state; a loaded game rolls with `OsRoller` until another
+/// roller is set.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Game {
+    card: Vec<ScoreCardData>,
+    dice: Option<Dice>,
+    phase: Phase,
+    /// Every roll and score so far, oldest first.
+    #[serde(default)]
+    log: Vec<Event>,
+    #[serde(skip, default = "default_roller")]
+    roller: Box<dyn DiceRoller>,
+}
+
+fn default_roller() -> Box<dyn DiceRoller> {
+    Box::new(OsRoller)
+}
+
+impl PartialEq for Game {
+    fn eq(&self, other: &Self) -> bool {
+        self.card == other.card
+            && self.dice == other.dice
+            && self.phase == other.phase
+            && self.log == other.log
+    }
+}
+
+impl Eq for Game {}
+
+impl Game {
+    pub fn new(rules: RuleSet) -> Self {
+        Self::with_card(vec![get_new_scorecard_data(rules)])
+    }
+
+    pub fn new_triple(rules: RuleSet) -> Self {
+        Self::with_card(triple::get_new_triple_card(&rules))
+    }
+
+    fn with_card(card: Vec<ScoreCardData>) -> Self {
+        Self {
+            card,
+            dice: None,
+            phase: Phase::AwaitingFirstRoll,
+            log: vec![],
+            roller: default_roller(),
+        }
+    }
+
+    /// Rolls every die from now on with `roller`, such as a `SeededRoller`
+    /// to make the game reproducible.
+    pub fn set_roller(&mut self, roller: Box<dyn DiceRoller>) {
+        self.roller = roller;
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    pub fn card(&self) -> &[ScoreCardData] {
+        &self.card
+    }
+
+    pub fn scorecard(&self) -> &ScoreCardData {
+        &self.card[0]
+    }
+
+    pub fn rules(&self) -> &RuleSet {
+        &self.card[0].rules
+    }
+
+    pub fn dice(&self) -> Option<&Dice> {
+        self.dice.as_ref()
+    }
+
+    pub fn is_triple(&self) -> bool {
+        self.card.len() > 1
+    }
+
+    pub fn grand_total(&self) -> i16 {
+        triple::grand_total(&self.card)
+    }
+
+    pub fn log(&self) -> &[Event] {
+        &self.log
+    }
+
+    /// Rolls the dice.  The first roll of a turn rolls every die and ignores
+    /// the mask; after that only the dice flagged in `mask` are rolled.
+    pub fn roll(&mut self, mask: &[bool]) -> Result<&Dice, GameError> {
+        let turn = self
+            .card
+            .iter()
+            .flat_map(|column| &column.line)
+            .filter(|line| line.value.is_some())
+            .count();
+        let roll = self.dice.as_ref().map_or(0, |d| d.roll_number as usize);
+        let mask = match self.phase {
+            Phase::AwaitingFirstRoll => {
+                self.roller.start_roll(turn, 1);
+                self.dice = Some(triple::next_hand(&mut self.card, self.roller.as_mut()));
+                vec![]
+            }
+            Phase::Rolling => {
+                let dice = self.dice.as_mut().ok_or(GameError::WrongPhase)?;
+                self.roller.start_roll(turn, roll + 1);
+                dice.reroll(mask, self.roller.as_mut())?;
+                mask.to_vec()
+            }
+            Phase::MustScore => return Err(GameError::NoRollsLeft),
+            Phase::GameOver => return Err(GameError::WrongPhase),
+        };
+
+        self.update_roll_phase();
+        let dice = self.dice.as_ref().ok_or(GameError::WrongPhase)?;
+        self.log.push(Event::Roll {
+            mask,
+            dice: dice.dice.clone(),
+        });
+        Ok(dice)
+    }
+
+    /// Turns the dice on the table to `faces`, as the `cheat` command does.
+    /// Before the first roll the faces stand for that roll.  Only the faces
+    /// change; the rolls left are still counted by the engine.
+    pub fn cheat(&mut self, faces: Vec<DieFace>) -> Result<(), GameError> {
+        if self.phase == Phase::GameOver {
+            return Err(GameError::WrongPhase);
+        }
+        if faces.len() != self.rules().number_of_dice {
+            return Err(GameError::DiceCount);
+        }
+
+        let mut dice = Dice::from_faces(faces)?;
+        if let Some(table) = &self.dice {
+            dice.rolls_left = table.rolls_left;
+            dice.roll_number = table.roll_number;
+        }
+        self.log.push(Event::SetDice { dice: dice.clone() });
+        self.dice = Some(dice);
+        self.update_roll_phase();
+        Ok(())
+    }
+
+    fn update_roll_phase(&mut self) {
+        let rolls_left = self.dice.as_ref().map_or(0, |d| d.rolls_left);
+        self.phase = if rolls_left > 0 {
+            Phase::Rolling
+        } else {
+            Phase::MustScore
+        };
+    }
+
+    pub fn score(&mut self, line: LineId) -> Result<i16, GameError> {
+        self.score_in(0, line)
+    }
+
+    pub fn score_in(&mut self, column: usize, line: LineId) -> Result<i16, GameError> {
+        let dice = match (self.phase, &self.dice) {
+            (Phase::Rolling, Some(dice)) | (Phase::MustScore, Some(dice)) => dice,
+            _ => return Err(GameError::WrongPhase),
+        };
+
+        let points = triple::play(&mut self.card, column, line, dice)?;
+
+        self.log.push(Event::Score {
+            column,
+            line,
+            points,
+        });
+        self.dice = None;
+        self.phase = if triple::game_over(&self.card) {
+            Phase::GameOver
+        } else {
+            Phase::AwaitingFirstRoll
+        };
+        Ok(points)
+    }
+
+    /// Plays one logged event through the rules again.
+    pub fn apply(&mut self, event: &Event) -> Result<(), GameError> {
+        match event {
+            Event::Roll { mask, .. } => {
+                let script = ScriptedRoller::new(event.rolled_faces())?;
+                let roller = std::mem::replace(&mut self.roller, Box::new(script));
+                let rolled = self.roll(mask).map(|_| ());
+                self.roller = roller;
+                rolled
+            }
+            Event::SetDice { dice } => self.cheat(dice.dice.clone()),
+            Event::Score { column, line, .. } => self.score_in(*column, *line).map(|_| ()),
+        }
+    }
+
+    /// The game as it stood before the first event and after every event
+    /// in the log, rebuilt from a fresh card so each step is checked again.
+    /// The last step must be this game, or the log is not to be trusted.
+    pub fn replay(&self) -> Result<Vec<Game>, GameError> {
+        let card = self
+            .card
+            .iter()
+            .map(|column| get_new_scorecard_data(column.rules.clone()))
+            .collect();
+        let mut game = Self::with_card(card);
+        let mut steps = vec![game.clone()];
+
+        for event in self.log.iter() {
+            game.apply(event)?;
+            steps.push(game.clone());
+        }
+        if steps.last() != Some(self) {
+            return Err(GameError::LogMismatch);
+        }
+        Ok(steps)
+    }
+
+    /// The points the dice on the table would score in every column and
+    /// line they may be played on.
+    pub fn preview(&self) -> Vec<(usize, LineId, i16)> {
+        let dice = match self.dice {
+            Some(ref dice) if self.phase != Phase::GameOver => dice,
+            _ => return vec![],
+        };
+
+        self.card
+            .iter()
+            .enumerate()
+            .flat_map(|(column, scorecard)| {
+                scorecard
+                    .preview(dice)
+                    .into_iter()
+                    .map(move |(line, points)| (column, line, points))
+            })
+            .collect()
+    }
+
+    pub fn legal_actions(&self) -> Vec<Action> {
+        let mut actions = vec![];
+
+        if self.phase == Phase::AwaitingFirstRoll || self.phase == Phase::Rolling {
+            actions.push(Action::Roll);
+        }
+
+        if let (Phase::Rolling, Some(dice)) | (Phase::MustScore, Some(dice)) =
+            (self.phase, &self.dice)
+        {
+            for (column, scorecard) in self.card.iter().enumerate() {
+                for line in scorecard.legal_lines(dice) {
+                    actions.push(Action::Score { column, line });
+                }
+            }
+        }
+        actions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::LineId as L;
+    use super::super::SeededRoller;
+    use super::*;
+
+    #[test]
+    fn new_game_awaits_first_roll() {
+        let game = Game::new(RuleSet::default());
+
+        assert_eq!(game.phase(), Phase::AwaitingFirstRoll);
+        assert_eq!(game.legal_actions(), vec![Action::Roll]);
+        assert!(game.dice().is_none());
+    }
+
+    #[test]
+    fn cannot_score_before_rolling() {
+        let mut game = Game::new(RuleSet::default());
+
+        assert_eq!(game.score(L::Chance), Err(GameError::WrongPhase));
+    }
+
+    #[test]
+    fn rolls_run_out() {
+        let mut game = Game::new(RuleSet::default());
+        let mask = vec![true; 5];
+
+        game.roll(&[]).unwrap();
+        assert_eq!(game.phase(), Phase::Rolling);
+        game.roll(&mask).unwrap();
+        game.roll(&mask).unwrap();
+        assert_eq!(game.phase(), Phase::MustScore);
+        assert_eq!(game.roll(&mask).err(), Some(GameError::NoRollsLeft));
+        assert!(!game.legal_actions().contains(&Action::Roll));
+    }
+
+    #[test]
+    fn reroll_mask_must_match_dice() {
+        let mut game = Game::new(RuleSet::default());
+
+        game.roll(&[]).unwrap();
+        assert_eq!(game.roll(&[true]).err(), Some(GameError::BadRerollMask));
+    }
+
+    #[test]
+    fn score_starts_next_turn() {
+        let mut game = Game::new(RuleSet::default());
+
+        game.roll(&[]).unwrap();
+        game.cheat(vec![2, 2, 3, 3, 3]).unwrap();
+        assert_eq!(game.score(L::FullHouse), Ok(25));
+        assert_eq!(game.phase(), Phase::AwaitingFirstRoll);
+        assert_eq!(game.score(L::Chance), Err(GameError::WrongPhase));
+    }
+
+    #[test]
+    fn cheating_only_changes_the_faces() {
+        let mut game = Game::new(RuleSet::default());
+        game.roll(&[]).unwrap();
+        game.roll(&[true; 5]).unwrap();
+
+        assert_eq!(game.cheat(vec![6; 4]), Err(GameError::DiceCount));
+        assert_eq!(game.cheat(vec![6; 6]), Err(GameError::DiceCount));
+        assert_eq!(
+            game.cheat(vec![6, 6, 6, 6, 7]),
+            Err(GameError::InvalidDieFace)
+        );
+        assert_eq!(
+            game.cheat(vec![0, 6, 6, 6, 6]),
+            Err(GameError::InvalidDieFace)
+        );
+
+        game.cheat(vec![6; 5]).unwrap();
+        let dice = game.dice().unwrap();
+        assert_eq!(dice.dice, vec![6; 5]);
+        assert_eq!((dice.rolls_left, dice.roll_number), (1, 2));
+    }
+
+    #[test]
+    fn legal_actions_follow_joker_rules() {
+        let mut game = Game::new(RuleSet::default());
+
+        game.cheat(vec![5, 5, 5, 5, 5]).unwrap();
+        game.score(L::Dice5).unwrap();
+        game.cheat(vec![5, 5, 5, 5, 5]).unwrap();
+
+        let expected = vec![
+            Action::Roll,
+            Action::Score {
+                column: 0,
+                line: L::Five,
+            },
+        ];
+        assert_eq!(game.legal_actions(), expected);
+    }
+
+    #[test]
+    fn filling_the_card_ends_the_game() {
+        let mut game = Game::new(RuleSet::default());
+        let lines = game.rules().lines();
+
+        for line in lines {
+            game.cheat(vec![1, 2, 3, 4, 6]).unwrap();
+            game.score(line).unwrap();
+        }
+        assert_eq!(game.phase(), Phase::GameOver);
+        assert!(game.legal_actions().is_empty());
+        assert_eq!(game.roll(&[]).err(), Some(GameError::WrongPhase));
+    }
+
+    #[test]
+    fn preview_matches_legal_actions() {
+        let mut game = Game::new_triple(RuleSet::default());
+        assert!(game.preview().is_empty());
+
+        game.cheat(vec![1, 2, 3, 4, 6]).unwrap();
+        let preview = game.preview();
+        assert!(preview.contains(&(2, L::SmallStraight, 30)));
+        assert_eq!(preview.len(), game.legal_actions().len() - 1);
+    }
+
+    #[test]
+    fn triple_game_scores_by_column() {
+        let mut game = Game::new_triple(RuleSet::default());
+
+        game.cheat(vec![1, 2, 3, 4, 6]).unwrap();
+        assert_eq!(game.score_in(2, L::Chance), Ok(16));
+        assert_eq!(game.grand_total(), 16 * 3);
+    }
+
+    #[test]
+    fn game_round_trips_through_json() {
+        let mut game = Game::new_triple(RuleSet::maxi());
+        game.roll(&[]).unwrap();
+        game.score_in(1, L::Chance).unwrap();
+        game.roll(&[]).unwrap();
+
+        let json = serde_json::to_string(&game).unwrap();
+        let loaded: Game = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded, game);
+    }
+
+    #[test]
+    fn game_round_trips_through_bincode() {
+        let mut game = Game::new(RuleSet::default());
+        game.cheat(vec![2, 2, 3, 3, 3]).unwrap();
+        game.score(L::FullHouse).unwrap();
+
+        let bytes = bincode::serialize(&game).unwrap();
+        let loaded: Game = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(loaded, game);
+        assert_eq!(loaded.scorecard().subtotal(L::GrandTotal), Ok(25));
+    }
+
+    #[test]
+    fn same_seed_plays_the_same_game() {
+        let play = |seed| {
+            let mut game = Game::new(RuleSet::default());
+            game.set_roller(Box::new(SeededRoller::new(seed)));
+            game.roll(&[]).unwrap();
+            game.roll(&[true, false, true, false, true]).unwrap();
+            game.score(L::Chance).unwrap();
+            game.roll(&[]).unwrap().clone()
+        };
+
+        assert_eq!(play(7), play(7));
+    }
+
+    #[test]
+    fn scripted_roller_deals_known_dice() {
+        let mut game = Game::new(RuleSet::default());
+        game.set_roller(Box::new(
+            ScriptedRoller::new(vec![3, 3, 3, 2, 2, 6]).unwrap(),
+        ));
+
+        game.roll(&[]).unwrap();
+        game.roll(&[false, false, false, true, false]).unwrap();
+        assert_eq!(game.dice().unwrap().dice, vec![3, 3, 3, 6, 2]);
+        assert_eq!(game.score(L::FullHouse), Ok(0));
+    }
+
+    #[test]
+    fn log_records_rolls_and_scores() {
+        let mut game = Game::new(RuleSet::default());
+        game.set_roller(Box::new(
+            ScriptedRoller::new(vec![1, 2, 3, 4, 5, 6]).unwrap(),
+        ));
+
+        game.roll(&[]).unwrap();
+        game.roll(&[true, false, false, false, false]).unwrap();
+        game.score(L::LargeStraight).unwrap();
+
+        let expected = vec![
+            Event::Roll {
+                mask: vec![],
+                dice: vec![1, 2, 3, 4, 5],
+            },
+            Event::Roll {
+                mask: vec![true, false, false, false, false],
+                dice: vec![6, 2, 3, 4, 5],
+            },
+            Event::Score {
+                column: 0,
+                line: L::LargeStraight,
+                points: 40,
+            },
+        ];
+        assert_eq!(game.log(), expected.as_slice());
+    }
+
+    #[test]
+    fn replay_rebuilds_every_step() {
+        let mut game = Game::new_triple(RuleSet::maxi());
+        game.set_roller(Box::new(SeededRoller::new(3)));
+        game.roll(&[]).unwrap();
+        game.roll(&[true, true, false, false, false, true]).unwrap();
+        game.score_in(2, L::Chance).unwrap();
+        game.roll(&[]).unwrap();
+        game.cheat(vec![6; 6]).unwrap();
+        game.score_in(0, L::Dice5).unwrap();
+
+        let steps = game.replay().unwrap();
+        assert_eq!(steps.len(), game.log().len() + 1);
+        assert_eq!(steps[0].phase(), Phase::AwaitingFirstRoll);
+        assert_eq!(steps.last(), Some(&game));
+        assert_eq!(steps[3].phase(), Phase::AwaitingFirstRoll);
+        assert_eq!(steps[3].grand_total(), steps[4].grand_total());
+    }
+
+    #[test]
+    fn replay_refuses_a_log_that_does_not_match() {
+        let mut game = Game::new(RuleSet::default());
+        game.roll(&[]).unwrap();
+        game.cheat(vec![2, 2, 2, 5, 5]).unwrap();
+        game.score(L::FullHouse).unwrap();
+
+        let mut cleared: Game =
+            serde_json::from_str(&serde_json::to_string(&game).unwrap()).unwrap();
+        cleared.log.clear();
+        assert_eq!(cleared.replay(), Err(GameError::LogMismatch));
+    }
+
+    #[test]
+    fn replay_refuses_a_logged_roll_off_the_die() {
+        let mut game = Game::new(RuleSet::default());
+        game.roll(&[]).unwrap();
+        game.log = vec![Event::Roll {
+            mask: vec![],
+            dice: vec![1, 2, 3, 4, 9],
+        }];
+        assert_eq!(game.replay(), Err(GameError::InvalidDieFace));
+    }
+}
@@ -1,12 +1,17 @@
-use rand::Rng;
+use super::{DiceRoller, GameError};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
 pub type DieFace = i8;
 
-#[derive(Debug)]
+/// The dice on the table.  Stable: the fields are read by front ends and
+/// bots, and new hands are built with `first_roll` or `from_faces`.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct Dice {
     pub dice: Vec<DieFace>,
     pub rolls_left: i8,
+    /// Which roll of the turn produced these dice, starting at 1.
+    pub roll_number: i8,
 }
 
 impl fmt::Display for Dice {
@@ -30,79 +35,150 @@ impl fmt::Display for Dice {
 }
 
 impl Dice {
-    pub const NUMBER_OF_DICE: usize = 5;
     pub const NUMBER_OF_FACES: i8 = 6;
     pub const ROLLS_PER_TURN: i8 = 3;
 
-    pub fn roll_die() -> DieFace {
-        let mut rng = rand::thread_rng();
-        rng.gen_range(0, Self::NUMBER_OF_FACES) + 1
-    }
-
-    #[allow(dead_code)]
-    pub fn roll_fake(dice: Vec<DieFace>) -> Self {
+    /// A hand of `dice` as dealt on the first roll, unchecked.  The engine
+    /// only builds these from faces it made itself.
+    pub(crate) fn roll_fake(dice: Vec<DieFace>) -> Self {
         Self {
             dice,
             rolls_left: Self::ROLLS_PER_TURN - 1,
+            roll_number: 1,
         }
     }
 
-    pub fn first_roll() -> Self {
-        let dice: Vec<_> = (0..Self::NUMBER_OF_DICE)
-            .map(|_i| Self::roll_die())
-            .collect();
+    /// Builds a hand from known faces, as dealt on the first roll.
+    pub fn from_faces(dice: Vec<DieFace>) -> Result<Self, GameError> {
+        if dice
+            .iter()
+            .any(|f| !(1..=Self::NUMBER_OF_FACES).contains(f))
+        {
+            return Err(GameError::InvalidDieFace);
+        }
+        Ok(Self::roll_fake(dice))
+    }
+
+    pub fn first_roll(number_of_dice: usize, roller: &mut dyn DiceRoller) -> Self {
+        let dice: Vec<_> = (0..number_of_dice).map(|i| roller.roll_die_at(i)).collect();
 
         Dice {
             dice,
             rolls_left: Self::ROLLS_PER_TURN - 1,
+            roll_number: 1,
         }
     }
 
-    pub fn reroll(&mut self, reroll: &[bool]) {
+    pub fn reroll(
+        &mut self,
+        reroll: &[bool],
+        roller: &mut dyn DiceRoller,
+    ) -> Result<(), GameError> {
         if self.dice.len() != reroll.len() {
-            panic!("dice.length and re-roll length must match");
+            return Err(GameError::BadRerollMask);
+        }
+        if self.rolls_left <= 0 {
+            return Err(GameError::NoRollsLeft);
         }
 
-        self.dice.iter_mut().zip(reroll).for_each(|(face, &flag)| {
+        for (i, (face, &flag)) in self.dice.iter_mut().zip(reroll).enumerate() {
             if flag {
-                *face = Self::roll_die()
+                *face = roller.roll_die_at(i)
             }
-        });
+        }
         self.rolls_left -= 1;
+        self.roll_number += 1;
+        Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::super::{OsRoller, ScriptedRoller};
     use super::*;
 
     #[test]
     fn first_roll_correct_number_of_dice() {
-        let hand = Dice::first_roll();
+        let hand = Dice::first_roll(5, &mut OsRoller);
 
-        assert_eq!(hand.dice.len(), Dice::NUMBER_OF_DICE);
+        assert_eq!(hand.dice.len(), 5);
         assert_eq!(hand.rolls_left, Dice::ROLLS_PER_TURN - 1);
     }
 
     #[test]
     fn re_roll_correct_number_of_dice() {
-        let mut hand = Dice::first_roll();
+        let mut hand = Dice::first_roll(5, &mut OsRoller);
         let reroll_flags: Vec<bool> = hand.dice.iter().map(|_i| true).collect();
 
-        assert_eq!(hand.dice.len(), Dice::NUMBER_OF_DICE);
+        assert_eq!(hand.dice.len(), 5);
         assert_eq!(hand.rolls_left, Dice::ROLLS_PER_TURN - 1);
 
-        hand.reroll(&reroll_flags);
-        assert_eq!(hand.dice.len(), Dice::NUMBER_OF_DICE);
+        hand.reroll(&reroll_flags, &mut OsRoller).unwrap();
+        assert_eq!(hand.dice.len(), 5);
         assert_eq!(hand.rolls_left, Dice::ROLLS_PER_TURN - 2);
+        assert_eq!(hand.roll_number, 2);
+    }
+
+    #[test]
+    fn first_roll_six_dice() {
+        let hand = Dice::first_roll(6, &mut OsRoller);
+
+        assert_eq!(hand.dice.len(), 6);
+    }
+
+    #[test]
+    fn re_roll_mask_length_mismatch() {
+        let mut hand = Dice::first_roll(5, &mut OsRoller);
+
+        assert_eq!(
+            hand.reroll(&[true, false], &mut OsRoller),
+            Err(GameError::BadRerollMask)
+        );
+        assert_eq!(hand.rolls_left, Dice::ROLLS_PER_TURN - 1);
+    }
+
+    #[test]
+    fn re_roll_no_rolls_left() {
+        let mut hand = Dice::first_roll(5, &mut OsRoller);
+        hand.rolls_left = 0;
+
+        assert_eq!(
+            hand.reroll(&[true; 5], &mut OsRoller),
+            Err(GameError::NoRollsLeft)
+        );
+    }
+
+    #[test]
+    fn from_faces_rejects_invalid_faces() {
+        assert_eq!(
+            Dice::from_faces(vec![1, 2, 7, 4, 5]).err(),
+            Some(GameError::InvalidDieFace)
+        );
+        assert_eq!(
+            Dice::from_faces(vec![0, 2, 3, 4, 5]).err(),
+            Some(GameError::InvalidDieFace)
+        );
+        assert_eq!(Dice::from_faces(vec![6; 5]).unwrap().dice, vec![6; 5]);
+    }
+
+    #[test]
+    fn re_roll_only_flagged_dice() {
+        let mut roller = ScriptedRoller::new(vec![1, 2, 3, 4, 5, 6, 6]).unwrap();
+        let mut hand = Dice::first_roll(5, &mut roller);
+
+        hand.reroll(&[false, true, false, false, true], &mut roller)
+            .unwrap();
+        assert_eq!(hand.dice, vec![1, 6, 3, 4, 6]);
     }
 
     #[test]
     fn roll_die_face_in_range() {
-        for _i in 0..1000 {
-            let die = Dice::roll_die();
-            assert!(!(die > 6), format!("die value too high {}", die));
-            assert!(!(die < 1), format!("die value too low {}", die));
+        for _i in 0..200 {
+            let hand = Dice::first_roll(5, &mut OsRoller);
+            for &die in hand.dice.iter() {
+                assert!(die <= 6, "die value too high {}", die);
+                assert!(die >= 1, "die value too low {}", die);
+            }
         }
     }
 }
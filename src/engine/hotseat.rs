@@ -0,0 +1,216 @@
+//! Several players taking turns at one table.
+
+use super::{Game, GameError, Phase, RuleSet};
+use serde::{Deserialize, Serialize};
+
+pub const MIN_PLAYERS: usize = 2;
+pub const MAX_PLAYERS: usize = 8;
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Player {
+    pub name: String,
+    pub game: Game,
+}
+
+/// The players in turn order and whose turn it is.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct HotSeat {
+    players: Vec<Player>,
+    turn: usize,
+}
+
+/// A place in the final ranking.  Players on the same total share a place,
+/// and the place after them is skipped: 1, 1, 3.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Standing {
+    pub place: usize,
+    pub name: String,
+    pub total: i16,
+    /// Won outright with a served Dice5, whatever the total.
+    pub won: bool,
+}
+
+impl HotSeat {
+    pub fn new(names: Vec<String>, rules: RuleSet) -> Result<Self, GameError> {
+        if names.len() < MIN_PLAYERS || names.len() > MAX_PLAYERS {
+            return Err(GameError::PlayerCount);
+        }
+
+        let players = names
+            .into_iter()
+            .map(|name| Player {
+                name,
+                game: Game::new(rules.clone()),
+            })
+            .collect();
+        Ok(Self { players, turn: 0 })
+    }
+
+    pub fn players(&self) -> &[Player] {
+        &self.players
+    }
+
+    pub fn players_mut(&mut self) -> &mut [Player] {
+        &mut self.players
+    }
+
+    /// The index of the player whose turn it is.
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+
+    pub fn current(&self) -> &Player {
+        &self.players[self.turn]
+    }
+
+    pub fn current_mut(&mut self) -> &mut Player {
+        &mut self.players[self.turn]
+    }
+
+    /// Over when every card is full, or as soon as someone wins outright.
+    pub fn is_over(&self) -> bool {
+        let won = self
+            .players
+            .iter()
+            .any(|p| p.game.card().iter().any(|c| c.won));
+        won || self
+            .players
+            .iter()
+            .all(|p| p.game.phase() == Phase::GameOver)
+    }
+
+    /// Passes the dice to the next player with lines left to fill.  Returns
+    /// `true` when that starts a new round.
+    pub fn next_turn(&mut self) -> bool {
+        let mut wrapped = false;
+        for _ in 0..self.players.len() {
+            self.turn = (self.turn + 1) % self.players.len();
+            wrapped |= self.turn == 0;
+            if self.current().game.phase() != Phase::GameOver {
+                break;
+            }
+        }
+        wrapped
+    }
+
+    pub fn standings(&self) -> Vec<Standing> {
+        let games: Vec<_> = self
+            .players
+            .iter()
+            .map(|p| (p.name.as_str(), &p.game))
+            .collect();
+        standings(&games)
+    }
+}
+
+/// Ranks any set of named games, best first.
+pub fn standings(games: &[(&str, &Game)]) -> Vec<Standing> {
+    let mut standings: Vec<Standing> = games
+        .iter()
+        .map(|&(name, game)| Standing {
+            place: 0,
+            name: name.to_string(),
+            total: game.grand_total(),
+            won: game.card().iter().any(|c| c.won),
+        })
+        .collect();
+    standings.sort_by_key(|s| (!s.won, -s.total));
+
+    for i in 0..standings.len() {
+        let tied = i > 0
+            && standings[i].won == standings[i - 1].won
+            && standings[i].total == standings[i - 1].total;
+        standings[i].place = if tied { standings[i - 1].place } else { i + 1 };
+    }
+    standings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::LineId as L;
+    use super::super::SeededRoller;
+    use super::*;
+
+    fn names(n: usize) -> Vec<String> {
+        (1..=n).map(|i| format!("P{}", i)).collect()
+    }
+
+    /// Plays the current player's turn straight into `line`.
+    fn take_turn(seat: &mut HotSeat, line: L) {
+        let game = &mut seat.current_mut().game;
+        game.roll(&[]).unwrap();
+        game.score(line).unwrap();
+    }
+
+    #[test]
+    fn two_to_eight_players() {
+        let rules = RuleSet::default();
+        assert_eq!(
+            HotSeat::new(names(1), rules.clone()),
+            Err(GameError::PlayerCount)
+        );
+        assert_eq!(
+            HotSeat::new(names(9), rules.clone()),
+            Err(GameError::PlayerCount)
+        );
+        assert_eq!(HotSeat::new(names(8), rules).unwrap().players().len(), 8);
+    }
+
+    #[test]
+    fn turns_rotate_and_rounds_wrap() {
+        let mut seat = HotSeat::new(names(3), RuleSet::default()).unwrap();
+        for (seed, player) in seat.players_mut().iter_mut().enumerate() {
+            player
+                .game
+                .set_roller(Box::new(SeededRoller::new(seed as u64)));
+        }
+
+        assert_eq!(seat.current().name, "P1");
+        take_turn(&mut seat, L::Chance);
+        assert!(!seat.next_turn());
+        assert_eq!(seat.turn(), 1);
+        take_turn(&mut seat, L::Chance);
+        assert!(!seat.next_turn());
+        take_turn(&mut seat, L::Chance);
+        assert!(seat.next_turn());
+        assert_eq!(seat.current().name, "P1");
+    }
+
+    #[test]
+    fn over_when_every_card_is_full() {
+        let mut seat = HotSeat::new(names(2), RuleSet::default()).unwrap();
+        let lines = RuleSet::default().lines();
+
+        for &line in &lines {
+            assert!(!seat.is_over());
+            take_turn(&mut seat, line);
+            seat.next_turn();
+            take_turn(&mut seat, line);
+            seat.next_turn();
+        }
+        assert!(seat.is_over());
+    }
+
+    #[test]
+    fn ties_share_a_place() {
+        let mut games = vec![];
+        for dice in [
+            vec![6, 6, 6, 6, 5],
+            vec![1, 1, 1, 1, 2],
+            vec![6, 6, 6, 5, 6],
+        ] {
+            let mut game = Game::new(RuleSet::default());
+            game.roll(&[]).unwrap();
+            game.cheat(dice).unwrap();
+            game.score(L::Chance).unwrap();
+            games.push(game);
+        }
+
+        let standings = standings(&[("Ann", &games[0]), ("Bob", &games[1]), ("Cy", &games[2])]);
+        let places: Vec<_> = standings
+            .iter()
+            .map(|s| (s.place, s.name.as_str(), s.total))
+            .collect();
+        assert_eq!(places, vec![(1, "Ann", 29), (1, "Cy", 29), (3, "Bob", 6)]);
+    }
+}
@@ -0,0 +1,36 @@
+use super::{Dice, DieFace, LineId};
+use serde::{Deserialize, Serialize};
+
+/// One step of a game, as recorded in `Game::log`.
+///
+/// The log holds everything needed to rebuild the game, so it settles what
+/// was rolled without trusting the roller.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Event {
+    /// The dice after a roll.  `mask` is empty for the first roll of a turn.
+    Roll { mask: Vec<bool>, dice: Vec<DieFace> },
+    /// The dice were replaced outright, as the `cheat` command does.
+    SetDice { dice: Dice },
+    Score {
+        column: usize,
+        line: LineId,
+        points: i16,
+    },
+}
+
+impl Event {
+    /// The faces a replay has to roll to land on the same dice: every die
+    /// for a first roll, else only the re-rolled ones, left to right.
+    pub fn rolled_faces(&self) -> Vec<DieFace> {
+        match self {
+            Event::Roll { mask, dice } if mask.is_empty() => dice.clone(),
+            Event::Roll { mask, dice } => dice
+                .iter()
+                .zip(mask)
+                .filter(|&(_, &flag)| flag)
+                .map(|(&face, _)| face)
+                .collect(),
+            _ => vec![],
+        }
+    }
+}
@@ -1,15 +1,91 @@
+pub mod advisor;
 mod calchand;
+pub mod combos;
+pub mod external;
+mod game;
 mod hand;
-
+pub mod hotseat;
+mod log;
+pub mod net;
+pub mod notation;
+pub mod odds;
+mod roller;
+mod rules;
+pub mod scores;
+pub mod solver;
+pub mod strategy;
+pub mod tournament;
+pub mod triple;
+
+pub use game::{Action, Game, Phase};
 pub use hand::{Dice, DieFace};
+pub use log::Event;
+pub use roller::{DiceRoller, KeyedRoller, OsRoller, ScriptedRoller, SeededRoller};
+pub use rules::{JokerRule, PlayOrder, RuleSet, Scoring};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
+/// Everything that can go wrong when driving the engine.
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
-pub enum SetError {
+#[non_exhaustive]
+pub enum GameError {
+    /// The line is not on this card.
+    UnknownLine,
     AlreadySet,
+    /// The Joker rules do not allow this line for a bonus Dice5.
+    IllegalJoker,
+    /// The play order requires a different line.
+    OutOfOrder,
+    UnknownColumn,
+    NoRollsLeft,
+    /// The re-roll mask does not have one flag per die.
+    BadRerollMask,
+    InvalidDieFace,
+    /// The action is not allowed in the current turn phase.
+    WrongPhase,
+    /// The solver cannot handle these rules, or a card of other rules.
+    UnsupportedRules,
+    /// More dice than the rules play with.
+    TooManyDice,
+    /// A hand with a different number of dice than the rules play with.
+    DiceCount,
+    /// A hot-seat game needs two to eight players.
+    PlayerCount,
+    /// Replaying the log does not end in the game that holds it.
+    LogMismatch,
+    /// Too many rolls to work the odds out exactly.
+    TooManyRolls,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            GameError::UnknownLine => "that line is not on this card",
+            GameError::AlreadySet => "that line has already been set",
+            GameError::IllegalJoker => "the Joker rules do not allow that line",
+            GameError::OutOfOrder => "lines must be played in order",
+            GameError::UnknownColumn => "there is no such column",
+            GameError::NoRollsLeft => "no rolls left",
+            GameError::BadRerollMask => "the re-roll mask must have one flag per die",
+            GameError::InvalidDieFace => "die faces must be 1 to 6",
+            GameError::WrongPhase => "not allowed at this point of the turn",
+            GameError::UnsupportedRules => "the solver does not support these rules",
+            GameError::TooManyDice => "more dice than these rules play with",
+            GameError::DiceCount => "these rules play with a different number of dice",
+            GameError::PlayerCount => "a hot-seat game takes 2 to 8 players",
+            GameError::LogMismatch => "the log does not lead to this game",
+            GameError::TooManyRolls => "too many rolls to work out the odds",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl std::error::Error for GameError {}
+
+/// A line on the card.  Stable, but new variants are added with new rule
+/// sets, so matches outside the engine need a wildcard arm.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+#[non_exhaustive]
 pub enum LineId {
     Ace,
     Two,
@@ -21,29 +97,58 @@ pub enum LineId {
     UpperBonus,
     UpperTotal,
 
+    OnePair,
+    TwoPairs,
+    ThreePairs,
     ThreeKind,
     FourKind,
     SmallStraight,
     LargeStraight,
+    FullStraight,
     FullHouse,
+    Castle,
+    Tower,
     Chance,
     Dice5,
+    DoubleDice5,
     BottomSubtotal,
     Dice5Bonus,
     GrandTotal,
 }
 
-// #[derive(Debug)]
-pub struct LineData {
-    pub id: LineId,
-    pub value: Option<i16>,
-    pub calc: fn(dice: &Dice, special_dice: bool) -> i16,
+impl LineId {
+    pub fn is_upper(self) -> bool {
+        matches!(
+            self,
+            LineId::Ace
+                | LineId::Two
+                | LineId::Three
+                | LineId::Four
+                | LineId::Five
+                | LineId::Six
+                | LineId::UpperSubtotal
+                | LineId::UpperBonus
+                | LineId::UpperTotal
+        )
+    }
+
+    pub fn upper_for_face(face: DieFace) -> Option<LineId> {
+        match face {
+            1 => Some(LineId::Ace),
+            2 => Some(LineId::Two),
+            3 => Some(LineId::Three),
+            4 => Some(LineId::Four),
+            5 => Some(LineId::Five),
+            6 => Some(LineId::Six),
+            _ => None,
+        }
+    }
 }
 
-// #[derive(Debug)]
-pub struct SubtotalData {
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct LineData {
     pub id: LineId,
-    pub calc: fn(scorecard: &ScoreCardData) -> i16,
+    pub value: Option<i16>,
 }
 
 impl fmt::Display for LineData {
@@ -58,72 +163,162 @@ impl fmt::Display for LineData {
     }
 }
 
+/// One column of the card.  Stable to read; change it only through `play`
+/// (or better, through `Game`) so the rules are checked.
+///
+/// Only the filled values are stored; how a line is scored comes from its
+/// `LineId` and the rule set, so a card can be saved and sent as data.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct ScoreCardData {
     pub line: Vec<LineData>,
-    pub calc_upper_subtotal: SubtotalData,
-    pub calc_upper_bonus: SubtotalData,
-    pub calc_upper_total: SubtotalData,
-    pub calc_dice5_bonus: SubtotalData,
-    pub calc_lower_subtotal: SubtotalData,
-    pub calc_grand_total: SubtotalData,
     pub bonus_dice5: i8,
+    pub saved_rolls: i8,
+    /// Set when the game was won outright by a served Dice5.
+    pub won: bool,
+    pub rules: RuleSet,
 }
 
 impl fmt::Display for ScoreCardData {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let out: Vec<_> = vec![
-            format!("{}", self.get_line_by_id(LineId::Ace)),
-            format!("{}", self.get_line_by_id(LineId::Two)),
-            format!("{}", self.get_line_by_id(LineId::Three)),
-            format!("{}", self.get_line_by_id(LineId::Four)),
-            format!("{}", self.get_line_by_id(LineId::Five)),
-            format!("{}", self.get_line_by_id(LineId::Six)),
-            "-------------------------".to_string(),
-            format!("{}", self.get_line_by_id(LineId::ThreeKind)),
-            format!("{}", self.get_line_by_id(LineId::FourKind)),
-            format!("{}", self.get_line_by_id(LineId::SmallStraight)),
-            format!("{}", self.get_line_by_id(LineId::LargeStraight)),
-            format!("{}", self.get_line_by_id(LineId::FullHouse)),
-            format!("{}", self.get_line_by_id(LineId::Chance)),
-            format!("{}", self.get_line_by_id(LineId::Dice5)),
-        ];
+        let upper = self.line.iter().filter(|l| l.id.is_upper());
+        let lower = self.line.iter().filter(|l| !l.id.is_upper());
+
+        let out: Vec<_> = upper
+            .map(|l| format!("{}", l))
+            .chain(std::iter::once("-------------------------".to_string()))
+            .chain(lower.map(|l| format!("{}", l)))
+            .collect();
 
         write!(f, "{}", out.join("\n"))
     }
 }
 
 impl ScoreCardData {
-    pub fn get_line_by_id(&self, zid: LineId) -> &LineData {
-        self.line.iter().find(|l| l.id == zid).expect("not found")
+    pub fn has_line(&self, zid: LineId) -> bool {
+        self.line.iter().any(|l| l.id == zid)
+    }
+
+    pub fn get_line_by_id(&self, zid: LineId) -> Result<&LineData, GameError> {
+        self.line
+            .iter()
+            .find(|l| l.id == zid)
+            .ok_or(GameError::UnknownLine)
+    }
+
+    /// A Dice5 rolled after the Dice5 box has been filled is a Joker.
+    pub fn is_joker(&self, hand: &Dice) -> bool {
+        if self.rules.joker == JokerRule::None {
+            return false;
+        }
+
+        let already_has_dice5 = self
+            .get_line_by_id(LineId::Dice5)
+            .is_ok_and(|l| l.value.is_some());
+        already_has_dice5 && calchand::is_dice5(hand)
+    }
+
+    /// The line the ordered rules require to be filled next, if any.
+    ///
+    /// In strict order this is the only legal target.  When only the upper
+    /// section is ordered the open lower lines may be used as well.
+    pub fn next_in_order(&self) -> Option<LineId> {
+        let mut open = self.line.iter().filter(|l| l.value.is_none());
+
+        match self.rules.order {
+            PlayOrder::Free => None,
+            PlayOrder::Strict => open.next().map(|l| l.id),
+            PlayOrder::UpperOnly => open.find(|l| l.id.is_upper()).map(|l| l.id),
+        }
+    }
+
+    /// The open lines the play order allows, before any Joker rules.
+    fn in_order_lines(&self) -> Vec<LineId> {
+        let next = self.next_in_order();
+        let upper_only = self.rules.order == PlayOrder::UpperOnly;
+
+        self.line
+            .iter()
+            .filter(|l| l.value.is_none())
+            .map(|l| l.id)
+            .filter(|&id| next.is_none() || next == Some(id) || (upper_only && !id.is_upper()))
+            .collect()
+    }
+
+    /// The open lines this hand may be played on.
+    ///
+    /// Under forced Joker rules a Joker must go in the matching upper line if
+    /// it is open, else in any open lower line (at full value), else in any
+    /// open upper line for zero.
+    pub fn legal_lines(&self, hand: &Dice) -> Vec<LineId> {
+        let open = self.in_order_lines();
+
+        if !self.is_joker(hand) || self.rules.joker == JokerRule::Free {
+            return open;
+        }
+
+        if let Some(upper) = hand.dice.first().and_then(|&f| LineId::upper_for_face(f)) {
+            if open.contains(&upper) {
+                return vec![upper];
+            }
+        }
+
+        let lower: Vec<_> = open.iter().cloned().filter(|id| !id.is_upper()).collect();
+        if lower.is_empty() {
+            open
+        } else {
+            lower
+        }
     }
 
-    pub fn play(&mut self, zid: LineId, hand: &Dice) -> Result<i16, SetError> {
-        let already_has_dice5 = self.get_line_by_id(LineId::Dice5).value.is_some();
-        let is_dice5 = calchand::is_dice5(hand);
-        let special_handling = already_has_dice5 && is_dice5;
+    pub fn play(&mut self, zid: LineId, hand: &Dice) -> Result<i16, GameError> {
+        let special_handling = self.is_joker(hand);
+        let earned_bonus = special_handling
+            && self
+                .get_line_by_id(LineId::Dice5)
+                .is_ok_and(|l| l.value.unwrap_or(0) > 0);
 
-        let point_result = self.get_points(zid, &hand, special_handling);
+        let point_result = self.get_points(zid, hand, special_handling);
+
+        if point_result.is_ok() && !self.in_order_lines().contains(&zid) {
+            return Err(GameError::OutOfOrder);
+        }
+
+        if point_result.is_ok() && !self.legal_lines(hand).contains(&zid) {
+            return Err(GameError::IllegalJoker);
+        }
 
         if let Ok(points) = point_result {
             self.set_val(zid, points)?;
-            if special_handling {
+            if earned_bonus {
                 self.bonus_dice5 += 1;
             }
+            if self.rules.save_rolls {
+                self.saved_rolls += hand.rolls_left;
+            }
+            if self.rules.served_dice5_wins && hand.roll_number == 1 && calchand::is_dice5(hand) {
+                self.won = true;
+            }
         }
         point_result
     }
 
-    pub fn set_val(&mut self, zid: LineId, value: i16) -> Result<(), SetError> {
+    pub fn take_saved_rolls(&mut self) -> i8 {
+        let saved = self.saved_rolls;
+        self.saved_rolls = 0;
+        saved
+    }
+
+    pub fn set_val(&mut self, zid: LineId, value: i16) -> Result<(), GameError> {
         let line = self.line.iter_mut().find(|l| l.id == zid);
 
         match line {
-            None => panic!("Set Val called with unknown id"),
+            None => Err(GameError::UnknownLine),
             Some(l) => match l.value {
                 None => {
                     l.value = Some(value);
                     Ok(())
                 }
-                _ => Err(SetError::AlreadySet),
+                _ => Err(GameError::AlreadySet),
             },
         }
     }
@@ -133,45 +328,69 @@ impl ScoreCardData {
         zid: LineId,
         hand: &Dice,
         dice5_bonus: bool,
-    ) -> Result<i16, SetError> {
-        let line = self.line.iter_mut().find(|l| l.id == zid);
+    ) -> Result<i16, GameError> {
+        let line = self.line.iter().find(|l| l.id == zid);
 
         match line {
-            None => panic!("Get points called with unknown id"),
+            None => Err(GameError::UnknownLine),
             Some(l) => match l.value {
-                None => Ok((l.calc)(&hand, dice5_bonus)),
-                _ => Err(SetError::AlreadySet),
+                None => Ok(self.points_for(l.id, hand, dice5_bonus)),
+                _ => Err(GameError::AlreadySet),
             },
         }
     }
 
-    pub fn game_over(&mut self) -> bool {
-        self.line.iter().all(|l| l.value.is_some())
+    fn points_for(&self, zid: LineId, hand: &Dice, dice5_bonus: bool) -> i16 {
+        let calc = calchand::get_calc(zid, self.rules.scoring);
+        calc(hand, &self.rules, dice5_bonus)
+    }
+
+    /// What `play` would score on every line this hand may be played on,
+    /// with the Joker rules applied.
+    pub fn preview(&self, hand: &Dice) -> Vec<(LineId, i16)> {
+        let joker = self.is_joker(hand);
+
+        self.legal_lines(hand)
+            .into_iter()
+            .map(|id| (id, self.points_for(id, hand, joker)))
+            .collect()
+    }
+
+    pub fn game_over(&self) -> bool {
+        self.won || self.line.iter().all(|l| l.value.is_some())
+    }
+
+    /// The value of a subtotal line such as `UpperBonus` or `GrandTotal`.
+    pub fn subtotal(&self, zid: LineId) -> Result<i16, GameError> {
+        match zid {
+            LineId::UpperSubtotal => Ok(calc_upper_subtotal(self)),
+            LineId::UpperBonus => Ok(calc_upper_bonus(self)),
+            LineId::UpperTotal => Ok(calc_upper_total(self)),
+            LineId::BottomSubtotal => Ok(calc_lower_subtotal(self)),
+            LineId::Dice5Bonus => Ok(calc_dice5_bonus(self)),
+            LineId::GrandTotal => Ok(calc_grand_total(self)),
+            _ => Err(GameError::UnknownLine),
+        }
     }
 }
 
-fn calc_subtotal(scorecard: &ScoreCardData, a: Vec<LineId>) -> i16 {
-    a.iter()
-        .flat_map(|&line_id| scorecard.get_line_by_id(line_id).value)
+fn calc_subtotal(scorecard: &ScoreCardData, upper: bool) -> i16 {
+    scorecard
+        .line
+        .iter()
+        .filter(|l| l.id.is_upper() == upper)
+        .flat_map(|l| l.value)
         .sum()
 }
 
 fn calc_upper_subtotal(scorecard: &ScoreCardData) -> i16 {
-    let a = vec![
-        LineId::Ace,
-        LineId::Two,
-        LineId::Three,
-        LineId::Four,
-        LineId::Five,
-        LineId::Six,
-    ];
-    calc_subtotal(&scorecard, a)
+    calc_subtotal(scorecard, true)
 }
 
 fn calc_upper_bonus(scorecard: &ScoreCardData) -> i16 {
     let upper_score = calc_upper_subtotal(scorecard);
-    if upper_score >= 63 {
-        35
+    if upper_score >= scorecard.rules.upper_bonus_threshold {
+        scorecard.rules.upper_bonus
     } else {
         0
     }
@@ -182,165 +401,106 @@ fn calc_upper_total(scorecard: &ScoreCardData) -> i16 {
 }
 
 fn calc_lower_subtotal(scorecard: &ScoreCardData) -> i16 {
-    let a = vec![
-        LineId::ThreeKind,
-        LineId::FourKind,
-        LineId::SmallStraight,
-        LineId::LargeStraight,
-        LineId::FullHouse,
-        LineId::Chance,
-        LineId::Dice5,
-    ];
-    calc_subtotal(&scorecard, a)
+    calc_subtotal(scorecard, false)
 }
 
 fn calc_dice5_bonus(scorecard: &ScoreCardData) -> i16 {
-    i16::from(scorecard.bonus_dice5) * 100
+    i16::from(scorecard.bonus_dice5) * scorecard.rules.dice5_bonus
 }
 
 fn calc_grand_total(scorecard: &ScoreCardData) -> i16 {
-    calc_upper_subtotal(scorecard) + calc_lower_subtotal(scorecard) + calc_dice5_bonus(scorecard)
+    calc_upper_total(scorecard) + calc_lower_subtotal(scorecard) + calc_dice5_bonus(scorecard)
 }
 
-pub fn get_new_scorecard_data() -> ScoreCardData {
-    let line_data = vec![
-        LineData {
-            id: LineId::Ace,
-            value: None,
-            calc: calchand::calc_ace,
-        },
-        LineData {
-            id: LineId::Two,
-            value: None,
-            calc: calchand::calc_two,
-        },
-        LineData {
-            id: LineId::Three,
-            value: None,
-            calc: calchand::calc_three,
-        },
-        LineData {
-            id: LineId::Four,
-            value: None,
-            calc: calchand::calc_four,
-        },
-        LineData {
-            id: LineId::Five,
-            value: None,
-            calc: calchand::calc_five,
-        },
-        LineData {
-            id: LineId::Six,
-            value: None,
-            calc: calchand::calc_six,
-        },
-        LineData {
-            id: LineId::ThreeKind,
-            value: None,
-            calc: calchand::calc_3k,
-        },
-        LineData {
-            id: LineId::FourKind,
-            value: None,
-            calc: calchand::calc_4k,
-        },
-        LineData {
-            id: LineId::SmallStraight,
-            value: None,
-            calc: calchand::calc_ss,
-        },
-        LineData {
-            id: LineId::LargeStraight,
-            value: None,
-            calc: calchand::calc_ls,
-        },
-        LineData {
-            id: LineId::FullHouse,
-            value: None,
-            calc: calchand::calc_fh,
-        },
-        LineData {
-            id: LineId::Chance,
-            value: None,
-            calc: calchand::calc_chance,
-        },
-        LineData {
-            id: LineId::Dice5,
-            value: None,
-            calc: calchand::calc_dice5,
-        },
-    ];
+pub fn get_new_scorecard_data(rules: RuleSet) -> ScoreCardData {
+    let line_data = rules
+        .lines()
+        .iter()
+        .map(|&id| LineData { id, value: None })
+        .collect();
 
     ScoreCardData {
         line: line_data,
-        calc_upper_subtotal: SubtotalData {
-            id: LineId::UpperSubtotal,
-            calc: calc_upper_subtotal,
-        },
-        calc_upper_bonus: SubtotalData {
-            id: LineId::UpperBonus,
-            calc: calc_upper_bonus,
-        },
-        calc_upper_total: SubtotalData {
-            id: LineId::UpperTotal,
-            calc: calc_upper_total,
-        },
-        calc_lower_subtotal: SubtotalData {
-            id: LineId::BottomSubtotal,
-            calc: calc_lower_subtotal,
-        },
-        calc_dice5_bonus: SubtotalData {
-            id: LineId::Dice5Bonus,
-            calc: calc_dice5_bonus,
-        },
-        calc_grand_total: SubtotalData {
-            id: LineId::GrandTotal,
-            calc: calc_grand_total,
-        },
         bonus_dice5: 0,
+        saved_rolls: 0,
+        won: false,
+        rules,
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::GameError as GErr;
     use super::LineId as L;
-    use super::SetError as SErr;
     use super::*;
 
+    #[test]
+    fn subtotal_lines_are_looked_up_by_id() {
+        let mut scorecard = get_new_scorecard_data(RuleSet::default());
+        scorecard.set_val(L::Six, 30).unwrap();
+        scorecard.set_val(L::Five, 25).unwrap();
+        scorecard.set_val(L::Four, 12).unwrap();
+        scorecard.set_val(L::Chance, 20).unwrap();
+
+        assert_eq!(scorecard.subtotal(L::UpperSubtotal), Ok(67));
+        assert_eq!(scorecard.subtotal(L::UpperBonus), Ok(35));
+        assert_eq!(scorecard.subtotal(L::UpperTotal), Ok(102));
+        assert_eq!(scorecard.subtotal(L::BottomSubtotal), Ok(20));
+        assert_eq!(scorecard.subtotal(L::GrandTotal), Ok(122));
+        assert_eq!(scorecard.subtotal(L::Chance), Err(GErr::UnknownLine));
+    }
+
+    #[test]
+    fn grand_total_counts_the_upper_bonus() {
+        for rules in [RuleSet::default(), RuleSet::house(), RuleSet::yatzy()] {
+            let mut scorecard = get_new_scorecard_data(rules.clone());
+            scorecard.set_val(L::Six, 24).unwrap();
+            scorecard.set_val(L::Five, 20).unwrap();
+            scorecard.set_val(L::Four, 16).unwrap();
+            assert_eq!(scorecard.subtotal(L::GrandTotal), Ok(60));
+
+            scorecard.set_val(L::Three, 3).unwrap();
+            assert_eq!(scorecard.subtotal(L::UpperSubtotal), Ok(63));
+            assert_eq!(
+                scorecard.subtotal(L::GrandTotal),
+                Ok(63 + rules.upper_bonus)
+            );
+        }
+    }
+
     #[test]
     fn get_new_scorecard_returns_card() {
-        let scorecard = get_new_scorecard_data();
-        let score = scorecard.get_line_by_id(L::Ace).value;
+        let scorecard = get_new_scorecard_data(RuleSet::default());
+        let score = scorecard.get_line_by_id(L::Ace).unwrap().value;
         assert_eq!(score, None);
     }
 
     #[test]
     fn set_score_short_name_exists_score_set() {
-        let mut scorecard = get_new_scorecard_data();
+        let mut scorecard = get_new_scorecard_data(RuleSet::default());
         let points = 99;
 
         let result = scorecard.set_val(L::Ace, points);
         match result {
-            Err(SErr::AlreadySet) => {
+            Err(_) => {
                 panic!("Already Set shoudln't happen");
             }
             Ok(_) => {
-                let p = scorecard.get_line_by_id(L::Ace).value.unwrap();
+                let p = scorecard.get_line_by_id(L::Ace).unwrap().value.unwrap();
                 assert_eq!(p, points);
-                assert!(true);
             }
         }
     }
 
     #[test]
     fn get_points() {
-        let mut scorecard = get_new_scorecard_data();
-        let dice = Dice::first_roll();
+        let mut scorecard = get_new_scorecard_data(RuleSet::default());
+        let dice = Dice::first_roll(5, &mut OsRoller);
 
         let result = scorecard.get_points(L::Chance, &dice, false);
 
         match result {
-            Err(SErr::AlreadySet) => {
+            Err(_) => {
                 panic!("Already Set shoudln't happen");
             }
             Ok(points) => {
@@ -351,22 +511,21 @@ mod tests {
 
     #[test]
     fn set_score_short_name_exists_score_set_twice() {
-        let mut scorecard = get_new_scorecard_data();
+        let mut scorecard = get_new_scorecard_data(RuleSet::default());
         let points1 = 99;
         let points2 = 32;
 
-        let result1 = scorecard.set_val(L::Ace, points1);
-        match result1 {
-            Err(SErr::AlreadySet) => {}
-            Ok(_) => {}
-        }
+        let _ = scorecard.set_val(L::Ace, points1);
 
         let result = scorecard.set_val(L::Ace, points2);
         match result {
-            Err(SErr::AlreadySet) => {
-                let p = scorecard.get_line_by_id(L::Ace).value.unwrap();
+            Err(GErr::AlreadySet) => {
+                let p = scorecard.get_line_by_id(L::Ace).unwrap().value.unwrap();
                 assert_eq!(p, points1);
             }
+            Err(_) => {
+                panic!("Only Already Set should happen");
+            }
             Ok(_) => {
                 panic!("OK shouldn't happen");
             }
@@ -375,18 +534,249 @@ mod tests {
 
     #[test]
     fn game_over_new_game() {
-        let mut scorecard = get_new_scorecard_data();
+        let scorecard = get_new_scorecard_data(RuleSet::default());
 
-        assert_eq!(false, scorecard.game_over());
+        assert!(!scorecard.game_over());
     }
 
     #[test]
     fn game_over_game_over() {
-        let mut scorecard = get_new_scorecard_data();
+        let mut scorecard = get_new_scorecard_data(RuleSet::default());
         for i in 0..scorecard.line.len() {
             scorecard.line[i].value = Some(4);
         }
 
-        assert_eq!(true, scorecard.game_over());
+        assert!(scorecard.game_over());
+    }
+
+    #[test]
+    fn joker_must_take_matching_upper_line() {
+        let mut scorecard = get_new_scorecard_data(RuleSet::default());
+        scorecard.set_val(L::Dice5, 50).unwrap();
+        let hand = Dice::roll_fake(vec![4, 4, 4, 4, 4]);
+
+        assert_eq!(scorecard.legal_lines(&hand), vec![L::Four]);
+        assert_eq!(scorecard.play(L::FullHouse, &hand), Err(GErr::IllegalJoker));
+        assert_eq!(scorecard.play(L::Four, &hand), Ok(20));
+        assert_eq!(scorecard.bonus_dice5, 1);
+    }
+
+    #[test]
+    fn preview_scores_every_open_line() {
+        let mut scorecard = get_new_scorecard_data(RuleSet::default());
+        scorecard.set_val(L::Chance, 20).unwrap();
+        let hand = Dice::roll_fake(vec![2, 3, 4, 5, 5]);
+        let preview = scorecard.preview(&hand);
+
+        assert_eq!(preview.len(), 12);
+        assert!(preview.contains(&(L::Five, 10)));
+        assert!(preview.contains(&(L::SmallStraight, 30)));
+        assert!(preview.contains(&(L::FourKind, 0)));
+        assert!(!preview.iter().any(|&(id, _)| id == L::Chance));
+    }
+
+    #[test]
+    fn preview_follows_joker_rules() {
+        let mut scorecard = get_new_scorecard_data(RuleSet::default());
+        scorecard.set_val(L::Dice5, 50).unwrap();
+        scorecard.set_val(L::Four, 16).unwrap();
+        let hand = Dice::roll_fake(vec![4, 4, 4, 4, 4]);
+        let preview = scorecard.preview(&hand);
+
+        assert!(preview.contains(&(L::LargeStraight, 40)));
+        assert!(preview.contains(&(L::FullHouse, 25)));
+        assert!(!preview.iter().any(|&(id, _)| id.is_upper()));
+    }
+
+    #[test]
+    fn joker_plays_lower_line_at_full_value() {
+        let mut scorecard = get_new_scorecard_data(RuleSet::default());
+        scorecard.set_val(L::Dice5, 50).unwrap();
+        scorecard.set_val(L::Four, 16).unwrap();
+        let hand = Dice::roll_fake(vec![4, 4, 4, 4, 4]);
+
+        assert_eq!(scorecard.play(L::Ace, &hand), Err(GErr::IllegalJoker));
+        assert_eq!(scorecard.play(L::LargeStraight, &hand), Ok(40));
+    }
+
+    #[test]
+    fn joker_plays_upper_line_for_zero_when_lower_full() {
+        let mut scorecard = get_new_scorecard_data(RuleSet::default());
+        for i in 0..scorecard.line.len() {
+            if !scorecard.line[i].id.is_upper() {
+                scorecard.line[i].value = Some(0);
+            }
+        }
+        scorecard.set_val(L::Four, 16).unwrap();
+        let hand = Dice::roll_fake(vec![4, 4, 4, 4, 4]);
+
+        assert_eq!(scorecard.play(L::Ace, &hand), Ok(0));
+        assert_eq!(scorecard.bonus_dice5, 0);
+    }
+
+    #[test]
+    fn first_dice5_is_not_a_joker() {
+        let mut scorecard = get_new_scorecard_data(RuleSet::default());
+        let hand = Dice::roll_fake(vec![4, 4, 4, 4, 4]);
+
+        assert!(!scorecard.is_joker(&hand));
+        assert_eq!(scorecard.play(L::FullHouse, &hand), Ok(0));
+    }
+
+    #[test]
+    fn free_joker_may_take_any_open_line() {
+        let mut scorecard = get_new_scorecard_data(RuleSet::classic());
+        scorecard.set_val(L::Dice5, 50).unwrap();
+        let hand = Dice::roll_fake(vec![4, 4, 4, 4, 4]);
+
+        assert_eq!(scorecard.play(L::FullHouse, &hand), Ok(25));
+        assert_eq!(scorecard.bonus_dice5, 1);
+    }
+
+    #[test]
+    fn house_rules_score_from_rule_set() {
+        let mut scorecard = get_new_scorecard_data(RuleSet::house());
+        let full_house = Dice::roll_fake(vec![2, 2, 3, 3, 3]);
+        assert_eq!(scorecard.play(L::FullHouse, &full_house), Ok(40));
+
+        scorecard.set_val(L::Ace, 3).unwrap();
+        scorecard.set_val(L::Two, 6).unwrap();
+        scorecard.set_val(L::Three, 9).unwrap();
+        scorecard.set_val(L::Four, 12).unwrap();
+        scorecard.set_val(L::Five, 15).unwrap();
+        scorecard.set_val(L::Six, 18).unwrap();
+        assert_eq!(calc_upper_bonus(&scorecard), 50);
+    }
+
+    #[test]
+    fn yatzy_card_has_no_joker() {
+        let mut scorecard = get_new_scorecard_data(RuleSet::yatzy());
+        scorecard.set_val(L::Dice5, 50).unwrap();
+        let hand = Dice::roll_fake(vec![4, 4, 4, 4, 4]);
+
+        assert!(!scorecard.is_joker(&hand));
+        assert_eq!(scorecard.play(L::FullHouse, &hand), Ok(0));
+        assert_eq!(scorecard.play(L::TwoPairs, &hand), Ok(0));
+        assert_eq!(scorecard.play(L::OnePair, &hand), Ok(8));
+        assert_eq!(scorecard.bonus_dice5, 0);
+    }
+
+    #[test]
+    fn lower_subtotal_includes_pair_lines() {
+        let mut scorecard = get_new_scorecard_data(RuleSet::yatzy());
+        scorecard.set_val(L::OnePair, 12).unwrap();
+        scorecard.set_val(L::Chance, 20).unwrap();
+        scorecard.set_val(L::Six, 18).unwrap();
+
+        assert_eq!(calc_lower_subtotal(&scorecard), 32);
+        assert_eq!(calc_upper_subtotal(&scorecard), 18);
+    }
+
+    #[test]
+    fn maxi_saves_unused_rolls() {
+        let mut scorecard = get_new_scorecard_data(RuleSet::maxi());
+        let hand = Dice::roll_fake(vec![1, 2, 3, 4, 5, 6]);
+
+        assert_eq!(scorecard.play(L::FullStraight, &hand), Ok(21));
+        assert_eq!(scorecard.saved_rolls, Dice::ROLLS_PER_TURN - 1);
+
+        assert_eq!(scorecard.take_saved_rolls(), Dice::ROLLS_PER_TURN - 1);
+        assert_eq!(scorecard.saved_rolls, 0);
+    }
+
+    #[test]
+    fn standard_rules_do_not_save_rolls() {
+        let mut scorecard = get_new_scorecard_data(RuleSet::default());
+        let hand = Dice::roll_fake(vec![1, 2, 3, 4, 5]);
+
+        scorecard.play(L::Chance, &hand).unwrap();
+        assert_eq!(scorecard.saved_rolls, 0);
+        assert_eq!(scorecard.take_saved_rolls(), 0);
+    }
+
+    #[test]
+    fn served_generala_wins_outright() {
+        let mut scorecard = get_new_scorecard_data(RuleSet::generala());
+        let hand = Dice::roll_fake(vec![3, 3, 3, 3, 3]);
+
+        assert_eq!(scorecard.play(L::Dice5, &hand), Ok(50));
+        assert!(scorecard.won);
+        assert!(scorecard.game_over());
+    }
+
+    #[test]
+    fn rolled_generala_does_not_win() {
+        let mut scorecard = get_new_scorecard_data(RuleSet::generala());
+        let mut hand = Dice::roll_fake(vec![3, 3, 3, 3, 3]);
+        hand.roll_number = 2;
+
+        assert_eq!(scorecard.play(L::Dice5, &hand), Ok(50));
+        assert!(!scorecard.game_over());
+    }
+
+    #[test]
+    fn strict_order_allows_only_next_line() {
+        let rules = RuleSet {
+            order: PlayOrder::Strict,
+            ..RuleSet::default()
+        };
+        let mut scorecard = get_new_scorecard_data(rules);
+        let hand = Dice::roll_fake(vec![1, 1, 2, 3, 4]);
+
+        assert_eq!(scorecard.next_in_order(), Some(L::Ace));
+        assert_eq!(scorecard.legal_lines(&hand), vec![L::Ace]);
+        assert_eq!(scorecard.play(L::Chance, &hand), Err(GErr::OutOfOrder));
+        assert_eq!(scorecard.play(L::Ace, &hand), Ok(2));
+        assert_eq!(scorecard.next_in_order(), Some(L::Two));
+    }
+
+    #[test]
+    fn upper_order_leaves_lower_section_free() {
+        let rules = RuleSet {
+            order: PlayOrder::UpperOnly,
+            ..RuleSet::default()
+        };
+        let mut scorecard = get_new_scorecard_data(rules);
+        let hand = Dice::roll_fake(vec![1, 1, 2, 3, 4]);
+
+        assert_eq!(scorecard.play(L::Two, &hand), Err(GErr::OutOfOrder));
+        assert_eq!(scorecard.play(L::SmallStraight, &hand), Ok(30));
+        assert_eq!(scorecard.play(L::Ace, &hand), Ok(2));
+        assert_eq!(scorecard.next_in_order(), Some(L::Two));
+    }
+
+    #[test]
+    fn strict_order_overrides_forced_joker() {
+        let rules = RuleSet {
+            order: PlayOrder::Strict,
+            ..RuleSet::default()
+        };
+        let mut scorecard = get_new_scorecard_data(rules);
+        for i in 0..scorecard.line.len() {
+            if scorecard.line[i].id != L::FullHouse && scorecard.line[i].id != L::Four {
+                scorecard.line[i].value = Some(0);
+            }
+        }
+        scorecard
+            .line
+            .iter_mut()
+            .find(|l| l.id == L::Dice5)
+            .unwrap()
+            .value = Some(50);
+        let hand = Dice::roll_fake(vec![4, 4, 4, 4, 4]);
+
+        assert_eq!(scorecard.legal_lines(&hand), vec![L::Four]);
+        assert_eq!(scorecard.play(L::FullHouse, &hand), Err(GErr::OutOfOrder));
+    }
+
+    #[test]
+    fn unknown_line_is_an_error() {
+        let mut scorecard = get_new_scorecard_data(RuleSet::default());
+        let hand = Dice::roll_fake(vec![2, 2, 5, 5, 6]);
+
+        assert!(scorecard.get_line_by_id(L::OnePair).is_err());
+        assert_eq!(scorecard.set_val(L::OnePair, 4), Err(GErr::UnknownLine));
+        assert_eq!(scorecard.play(L::OnePair, &hand), Err(GErr::UnknownLine));
+        assert_eq!(scorecard.play(L::GrandTotal, &hand), Err(GErr::UnknownLine));
     }
 }
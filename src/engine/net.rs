@@ -0,0 +1,418 @@
+//! Hot-seat games hosted over TCP, one JSON message per line.
+//!
+//! The server owns the dice.  Clients can only ask to roll or to score, and
+//! every request is played through the engine before anyone sees it, so a
+//! client has no way to choose its dice.
+
+use super::hotseat::{HotSeat, MAX_PLAYERS, MIN_PLAYERS};
+use super::{GameError, LineId, Phase, RuleSet, SeededRoller};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+/// How long a new connection has to send `Join`, so one that stays silent
+/// cannot hold up everyone joining after it.
+const JOIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The longest line the server reads.  Requests are far shorter.
+const MAX_REQUEST: u64 = 4 * 1024;
+
+/// The longest line a client reads.  A table of eight is far shorter.
+const MAX_REPLY: u64 = 1024 * 1024;
+
+/// What a client asks of the server.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Request {
+    /// The first message on a connection.
+    Join {
+        name: String,
+    },
+    /// Re-rolls the dice flagged in `mask`.
+    Roll {
+        mask: Vec<bool>,
+    },
+    Score {
+        line: LineId,
+    },
+}
+
+/// What the server tells its clients.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Reply {
+    /// The answer to `Join`: the client's place in the turn order.
+    Welcome { seat: usize },
+    /// The whole table, sent to everyone after every move.
+    Table { table: HotSeat },
+    /// The request was not played.  The reason is meant for people.
+    Refused { reason: String },
+    /// A player hung up and the game is abandoned.
+    Left { name: String },
+}
+
+fn send<T: Serialize>(stream: &mut TcpStream, message: &T) -> io::Result<()> {
+    let mut line = serde_json::to_string(message).map_err(io::Error::other)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())
+}
+
+/// Reads one message of at most `limit` bytes.  A line that is not a
+/// message is `InvalidData`, and the connection is still usable after it.
+/// A longer line is an error the connection does not recover from.
+fn receive<T: DeserializeOwned>(reader: &mut impl BufRead, limit: u64) -> io::Result<T> {
+    let mut line = String::new();
+    let read = reader.by_ref().take(limit).read_line(&mut line)?;
+    if read == 0 {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    if read as u64 == limit && !line.ends_with('\n') {
+        return Err(io::Error::other("the message is too long"));
+    }
+    serde_json::from_str(&line).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Sends to every client.  A client that cannot be reached is noticed by
+/// its reader instead.
+fn broadcast(streams: &mut [TcpStream], reply: &Reply) {
+    for stream in streams {
+        send(stream, reply).ok();
+    }
+}
+
+pub struct Server {
+    listener: TcpListener,
+    rules: RuleSet,
+    players: usize,
+    seed: Option<u64>,
+}
+
+impl Server {
+    /// Listens on `addr` for a game of `players`.  Port 0 picks a free port.
+    pub fn bind<A: ToSocketAddrs>(addr: A, rules: RuleSet, players: usize) -> io::Result<Self> {
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&players) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                GameError::PlayerCount,
+            ));
+        }
+
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            rules,
+            players,
+            seed: None,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Rolls the player in seat `n` from `seed - n`, as hot-seat games do.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+    }
+
+    /// Waits for every player to join, plays one game to the end and
+    /// returns the final table.  A connection that does not join in time, or
+    /// cannot be welcomed, is dropped.  A player hanging up abandons the
+    /// game with `ConnectionAborted`.
+    pub fn run(self) -> io::Result<HotSeat> {
+        let (sender, requests) = mpsc::channel();
+        let mut streams = vec![];
+        let mut names = vec![];
+
+        while streams.len() < self.players {
+            let (mut stream, _) = self.listener.accept()?;
+            let mut reader = match stream.try_clone() {
+                Ok(clone) => BufReader::new(clone),
+                Err(_) => continue,
+            };
+            if stream.set_read_timeout(Some(JOIN_TIMEOUT)).is_err() {
+                continue;
+            }
+            let name = match receive(&mut reader, MAX_REQUEST) {
+                Ok(Request::Join { name }) if !name.trim().is_empty() => name,
+                Ok(Request::Join { .. }) => format!("Player {}", streams.len() + 1),
+                _ => continue,
+            };
+
+            let seat = streams.len();
+            let welcomed = stream
+                .set_read_timeout(None)
+                .and_then(|()| send(&mut stream, &Reply::Welcome { seat }));
+            if welcomed.is_err() {
+                continue;
+            }
+            streams.push(stream);
+            names.push(name);
+
+            let sender = sender.clone();
+            thread::spawn(move || loop {
+                let request: io::Result<Request> = receive(&mut reader, MAX_REQUEST);
+                let hung_up =
+                    matches!(&request, Err(err) if err.kind() != io::ErrorKind::InvalidData);
+                if sender.send((seat, request)).is_err() || hung_up {
+                    break;
+                }
+            });
+        }
+
+        let mut table = HotSeat::new(names, self.rules.clone())
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+        if let Some(seed) = self.seed {
+            for (n, player) in table.players_mut().iter_mut().enumerate() {
+                let seed = seed.wrapping_sub(n as u64);
+                player.game.set_roller(Box::new(SeededRoller::new(seed)));
+            }
+        }
+        first_roll(&mut table);
+        broadcast(
+            &mut streams,
+            &Reply::Table {
+                table: table.clone(),
+            },
+        );
+
+        while !table.is_over() {
+            let (seat, request) = requests.recv().map_err(io::Error::other)?;
+            let refusal = match request {
+                Ok(request) if seat == table.turn() => play(&mut table, request).err(),
+                Ok(_) => Some(format!("it is {}'s turn", table.current().name)),
+                Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+                    Some(format!("not understood: {}", err))
+                }
+                Err(_) => {
+                    let name = table.players()[seat].name.clone();
+                    broadcast(&mut streams, &Reply::Left { name: name.clone() });
+                    return Err(io::Error::new(
+                        io::ErrorKind::ConnectionAborted,
+                        format!("{} left the game", name),
+                    ));
+                }
+            };
+
+            match refusal {
+                Some(reason) => {
+                    send(&mut streams[seat], &Reply::Refused { reason }).ok();
+                }
+                None => broadcast(
+                    &mut streams,
+                    &Reply::Table {
+                        table: table.clone(),
+                    },
+                ),
+            }
+        }
+        Ok(table)
+    }
+}
+
+/// Rolls for the player whose turn it is, if they have not rolled yet.
+fn first_roll(table: &mut HotSeat) {
+    let game = &mut table.current_mut().game;
+    if game.phase() == Phase::AwaitingFirstRoll {
+        game.roll(&[]).expect("a new turn can roll");
+    }
+}
+
+/// Plays the current player's request, passing the dice on after a score.
+fn play(table: &mut HotSeat, request: Request) -> Result<(), String> {
+    let game = &mut table.current_mut().game;
+    match request {
+        Request::Join { .. } => return Err("already joined".to_string()),
+        Request::Roll { mask } => {
+            game.roll(&mask).map_err(|err| err.to_string())?;
+        }
+        Request::Score { line } => {
+            game.score(line).map_err(|err| err.to_string())?;
+            table.next_turn();
+            if !table.is_over() {
+                first_roll(table);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// One player's connection to a server.
+pub struct Client {
+    stream: TcpStream,
+    reader: BufReader<TcpStream>,
+    seat: usize,
+}
+
+impl Client {
+    /// Joins the game at `addr` as `name`.
+    pub fn connect<A: ToSocketAddrs>(addr: A, name: &str) -> io::Result<Self> {
+        let mut stream = TcpStream::connect(addr)?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let join = Request::Join {
+            name: name.to_string(),
+        };
+        send(&mut stream, &join)?;
+
+        match receive(&mut reader, MAX_REPLY)? {
+            Reply::Welcome { seat } => Ok(Self {
+                stream,
+                reader,
+                seat,
+            }),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the server did not welcome us",
+            )),
+        }
+    }
+
+    /// This client's place in the turn order.
+    pub fn seat(&self) -> usize {
+        self.seat
+    }
+
+    pub fn send(&mut self, request: &Request) -> io::Result<()> {
+        send(&mut self.stream, request)
+    }
+
+    /// Waits for the next message from the server.
+    pub fn receive(&mut self) -> io::Result<Reply> {
+        receive(&mut self.reader, MAX_REPLY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A server for `players` on a free localhost port, running in the
+    /// background.
+    fn serve(players: usize) -> (SocketAddr, thread::JoinHandle<io::Result<HotSeat>>) {
+        let mut server = Server::bind("127.0.0.1:0", RuleSet::default(), players).unwrap();
+        server.set_seed(5);
+        let addr = server.local_addr().unwrap();
+        (addr, thread::spawn(move || server.run()))
+    }
+
+    fn table(client: &mut Client) -> HotSeat {
+        match client.receive().unwrap() {
+            Reply::Table { table } => table,
+            reply => panic!("expected the table, got {:?}", reply),
+        }
+    }
+
+    fn refused(client: &mut Client) -> bool {
+        matches!(client.receive().unwrap(), Reply::Refused { .. })
+    }
+
+    #[test]
+    fn two_players_finish_a_game() {
+        let (addr, server) = serve(2);
+        let mut clients = [
+            Client::connect(addr, "Ann").unwrap(),
+            Client::connect(addr, "Bob").unwrap(),
+        ];
+        assert_eq!(clients[1].seat(), 1);
+
+        let mut seat = table(&mut clients[0]);
+        assert_eq!(table(&mut clients[1]), seat);
+        for line in RuleSet::default().lines() {
+            for player in 0..2 {
+                assert_eq!(seat.turn(), player);
+                clients[player].send(&Request::Score { line }).unwrap();
+                seat = table(&mut clients[0]);
+                assert_eq!(table(&mut clients[1]), seat);
+            }
+        }
+
+        assert!(seat.is_over());
+        assert_eq!(server.join().unwrap().unwrap(), seat);
+        let names: Vec<_> = seat.players().iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["Ann", "Bob"]);
+    }
+
+    #[test]
+    fn every_move_is_checked() {
+        let (addr, _server) = serve(2);
+        let mut ann = Client::connect(addr, "Ann").unwrap();
+        let mut bob = Client::connect(addr, "Bob").unwrap();
+        let before = table(&mut ann);
+        table(&mut bob);
+
+        bob.send(&Request::Roll {
+            mask: vec![true; 5],
+        })
+        .unwrap();
+        assert!(refused(&mut bob));
+        ann.send(&Request::Roll { mask: vec![true] }).unwrap();
+        assert!(refused(&mut ann));
+
+        // There is no request for setting the dice.
+        writeln!(ann.stream, "{{\"SetDice\":{{\"dice\":[6,6,6,6,6]}}}}").unwrap();
+        assert!(refused(&mut ann));
+
+        ann.send(&Request::Roll {
+            mask: vec![true; 5],
+        })
+        .unwrap();
+        let after = table(&mut ann);
+        assert_eq!(after.current().name, "Ann");
+        assert_eq!(after.current().game.dice().unwrap().rolls_left, 1);
+        assert_ne!(after, before);
+    }
+
+    #[test]
+    fn a_player_hanging_up_ends_the_game() {
+        let (addr, server) = serve(2);
+        let mut ann = Client::connect(addr, "Ann").unwrap();
+        let bob = Client::connect(addr, "Bob").unwrap();
+        table(&mut ann);
+        drop(bob);
+
+        loop {
+            match ann.receive().unwrap() {
+                Reply::Left { name } => break assert_eq!(name, "Bob"),
+                Reply::Table { .. } => {}
+                reply => panic!("unexpected {:?}", reply),
+            }
+        }
+        let err = server.join().unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::ConnectionAborted);
+    }
+
+    #[test]
+    fn connections_that_do_not_join_are_dropped() {
+        let (addr, server) = serve(2);
+        drop(TcpStream::connect(addr).unwrap());
+        let mut garbled = TcpStream::connect(addr).unwrap();
+        writeln!(garbled, "hello").unwrap();
+
+        let mut ann = Client::connect(addr, "Ann").unwrap();
+        let bob = Client::connect(addr, "Bob").unwrap();
+        assert_eq!((ann.seat(), bob.seat()), (0, 1));
+        assert_eq!(table(&mut ann).players().len(), 2);
+        drop(bob);
+        assert!(server.join().unwrap().is_err());
+    }
+
+    #[test]
+    fn an_overlong_line_is_cut_off() {
+        let (addr, server) = serve(2);
+        let mut ann = Client::connect(addr, "Ann").unwrap();
+        let _bob = Client::connect(addr, "Bob").unwrap();
+        table(&mut ann);
+
+        let long = "x".repeat(MAX_REQUEST as usize * 2);
+        writeln!(ann.stream, "{}", long).unwrap();
+        let err = server.join().unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::ConnectionAborted);
+    }
+
+    #[test]
+    fn two_to_eight_players() {
+        assert!(Server::bind("127.0.0.1:0", RuleSet::default(), 1).is_err());
+        assert!(Server::bind("127.0.0.1:0", RuleSet::default(), 9).is_err());
+    }
+}
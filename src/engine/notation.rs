@@ -0,0 +1,521 @@
+//! A line-oriented notation for whole games, in the spirit of chess PGN.
+//!
+//! ```text
+//! [Rules "modern"]
+//! [Player "Ann"]
+//! [Seed "42"]
+//!
+//! T1: 3 3 5 1 6 / keep 33 / 3 3 3 2 4 / 3k=15
+//! T2: 6 6 6 6 6 / d=50
+//! ```
+//!
+//! A turn lists the dice after every roll, the dice kept before each
+//! re-roll (`keep -` when none are), dice replaced outright (`set 6 6 6 6 6`)
+//! and the line played with its points.  Lines use the short codes from
+//! `text::get_short_name`; triple games add the column, as in `fh@2=25`.
+//! With several players each turn names its player: `T1 Ann: ...`.
+//!
+//! Headers other than `Rules`, `Order`, `Columns`, `Player` and `Seed` are
+//! ignored, so older readers accept games written by newer versions.
+
+use super::{Dice, DieFace, Event, Game, LineId, PlayOrder, RuleSet};
+use crate::text;
+use std::fmt;
+
+/// A game, or a game for each player, with the headers that go with it.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Record {
+    pub players: Vec<String>,
+    pub seed: Option<u64>,
+    /// One game per player, or a single game when no players are named.
+    pub games: Vec<Game>,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseError {
+    /// The line of the text the error was found on, counting from 1.
+    pub line: usize,
+    pub reason: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+pub(super) fn order_name(order: PlayOrder) -> &'static str {
+    match order {
+        PlayOrder::Free => "free",
+        PlayOrder::Strict => "strict",
+        PlayOrder::UpperOnly => "upper",
+    }
+}
+
+fn order_by_name(name: &str) -> Option<PlayOrder> {
+    match name {
+        "free" => Some(PlayOrder::Free),
+        "strict" => Some(PlayOrder::Strict),
+        "upper" => Some(PlayOrder::UpperOnly),
+        _ => None,
+    }
+}
+
+fn faces_text(dice: &[DieFace]) -> String {
+    let faces: Vec<_> = dice.iter().map(|f| f.to_string()).collect();
+    faces.join(" ")
+}
+
+/// The log split into turns; each turn but the last ends with a score.
+fn turns(log: &[Event]) -> Vec<&[Event]> {
+    let mut turns = vec![];
+    let mut start = 0;
+
+    for (i, event) in log.iter().enumerate() {
+        if let Event::Score { .. } = event {
+            turns.push(&log[start..=i]);
+            start = i + 1;
+        }
+    }
+    if start < log.len() {
+        turns.push(&log[start..]);
+    }
+    turns
+}
+
+fn write_turn(turn: &[Event], triple: bool) -> String {
+    let mut parts = vec![];
+    let mut prev: Vec<DieFace> = vec![];
+
+    for event in turn {
+        match event {
+            Event::Roll { mask, dice } if mask.is_empty() => {
+                parts.push(faces_text(dice));
+                prev = dice.clone();
+            }
+            Event::Roll { mask, dice } => {
+                let kept: String = prev
+                    .iter()
+                    .zip(mask)
+                    .filter(|&(_, &flag)| !flag)
+                    .map(|(face, _)| face.to_string())
+                    .collect();
+                if kept.is_empty() {
+                    parts.push("keep -".to_string());
+                } else {
+                    parts.push(format!("keep {}", kept));
+                }
+                parts.push(faces_text(dice));
+                prev = dice.clone();
+            }
+            Event::SetDice { dice } => {
+                parts.push(format!("set {}", faces_text(&dice.dice)));
+                prev = dice.dice.clone();
+            }
+            Event::Score {
+                column,
+                line,
+                points,
+            } => {
+                let code = text::get_short_name(*line);
+                if triple {
+                    parts.push(format!("{}@{}={}", code, column + 1, points));
+                } else {
+                    parts.push(format!("{}={}", code, points));
+                }
+            }
+        }
+    }
+    parts.join(" / ")
+}
+
+pub fn write(record: &Record) -> String {
+    let mut out = vec![];
+    let first = match record.games.first() {
+        Some(game) => game,
+        None => return String::new(),
+    };
+    let rules = first.rules();
+
+    out.push(format!("[Rules \"{}\"]", rules.name));
+    if rules.order != PlayOrder::Free {
+        out.push(format!("[Order \"{}\"]", order_name(rules.order)));
+    }
+    if first.is_triple() {
+        out.push(format!("[Columns \"{}\"]", first.card().len()));
+    }
+    for player in record.players.iter() {
+        out.push(format!("[Player \"{}\"]", player));
+    }
+    if let Some(seed) = record.seed {
+        out.push(format!("[Seed \"{}\"]", seed));
+    }
+    out.push(String::new());
+
+    let all_turns: Vec<_> = record.games.iter().map(|g| turns(g.log())).collect();
+    let most = all_turns.iter().map(|t| t.len()).max().unwrap_or(0);
+
+    for n in 0..most {
+        for (player, game_turns) in all_turns.iter().enumerate() {
+            if let Some(turn) = game_turns.get(n) {
+                let label = match record.players.get(player) {
+                    Some(name) if record.games.len() > 1 => format!("T{} {}", n + 1, name),
+                    _ => format!("T{}", n + 1),
+                };
+                out.push(format!(
+                    "{}: {}",
+                    label,
+                    write_turn(turn, first.is_triple())
+                ));
+            }
+        }
+    }
+
+    let mut text = out.join("\n");
+    text.push('\n');
+    text
+}
+
+fn parse_header(line: &str) -> Option<(&str, &str)> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?;
+    let space = inner.find(' ')?;
+    let value = inner[space..].trim();
+    let value = value.strip_prefix('"')?.strip_suffix('"')?;
+    Some((&inner[..space], value))
+}
+
+fn parse_faces(text: &str) -> Result<Vec<DieFace>, String> {
+    text.split_whitespace()
+        .map(|f| {
+            f.parse::<DieFace>()
+                .ok()
+                .filter(|f| (1..=Dice::NUMBER_OF_FACES).contains(f))
+                .ok_or_else(|| format!("'{}' is not a die face", f))
+        })
+        .collect()
+}
+
+/// The re-roll mask that turns `prev` into `dice` while keeping `kept`.
+fn mask_for_keep(prev: &[DieFace], kept: &str, dice: &[DieFace]) -> Result<Vec<bool>, String> {
+    let mut counts = [0; Dice::NUMBER_OF_FACES as usize + 1];
+    if kept != "-" {
+        for c in kept.chars() {
+            match c.to_digit(10) {
+                Some(face) if (1..=Dice::NUMBER_OF_FACES as u32).contains(&face) => {
+                    counts[face as usize] += 1
+                }
+                _ => return Err(format!("cannot keep '{}'", c)),
+            }
+        }
+    }
+
+    if prev.len() != dice.len() {
+        return Err("the number of dice changed".to_string());
+    }
+
+    let mask: Vec<bool> = prev
+        .iter()
+        .zip(dice)
+        .map(|(&before, &after)| {
+            let count = &mut counts[before as usize];
+            if before == after && *count > 0 {
+                *count -= 1;
+                false
+            } else {
+                true
+            }
+        })
+        .collect();
+
+    if counts.iter().any(|&c| c > 0) {
+        return Err(format!("the kept dice {} are not on the table", kept));
+    }
+    Ok(mask)
+}
+
+fn parse_score(part: &str) -> Result<(usize, LineId, i16), String> {
+    let (target, points) = match part.find('=') {
+        Some(i) => (&part[..i], &part[i + 1..]),
+        None => return Err(format!("I have no idea what this means: {}", part)),
+    };
+    let (code, column) = match target.find('@') {
+        Some(i) => (&target[..i], &target[i + 1..]),
+        None => (target, "1"),
+    };
+
+    let line = text::get_id_by_short_name(code).ok_or(format!("unknown line '{}'", code))?;
+    let column = column
+        .parse::<usize>()
+        .ok()
+        .filter(|&c| c >= 1)
+        .ok_or(format!("bad column '{}'", column))?;
+    let points = points
+        .parse::<i16>()
+        .map_err(|_| format!("bad points '{}'", points))?;
+    Ok((column - 1, line, points))
+}
+
+/// The events of one turn, in the order they were played.
+fn parse_turn(text: &str) -> Result<Vec<Event>, String> {
+    let mut events = vec![];
+    let mut prev: Vec<DieFace> = vec![];
+    let mut keep: Option<&str> = None;
+
+    for part in text.split('/').map(|p| p.trim()) {
+        if let Some(kept) = part.strip_prefix("keep ") {
+            keep = Some(kept.trim());
+        } else if let Some(faces) = part.strip_prefix("set ") {
+            let faces = parse_faces(faces)?;
+            let dice = Dice::from_faces(faces.clone()).map_err(|e| e.to_string())?;
+            events.push(Event::SetDice { dice });
+            prev = faces;
+        } else if part.starts_with(|c: char| c.is_ascii_digit()) && !part.contains('=') {
+            let dice = parse_faces(part)?;
+            let mask = match keep.take() {
+                Some(kept) => mask_for_keep(&prev, kept, &dice)?,
+                None => vec![],
+            };
+            events.push(Event::Roll {
+                mask,
+                dice: dice.clone(),
+            });
+            prev = dice;
+        } else {
+            let (column, line, points) = parse_score(part)?;
+            events.push(Event::Score {
+                column,
+                line,
+                points,
+            });
+        }
+    }
+
+    if keep.is_some() {
+        return Err("a keep must be followed by the dice rolled".to_string());
+    }
+    Ok(events)
+}
+
+fn new_games(rules: &RuleSet, columns: usize, players: usize) -> Vec<Game> {
+    (0..players.max(1))
+        .map(|_| match columns {
+            1 => Game::new(rules.clone()),
+            _ => Game::new_triple(rules.clone()),
+        })
+        .collect()
+}
+
+/// Reads a game written by `write`, playing every turn through the rules.
+pub fn parse(input: &str) -> Result<Record, ParseError> {
+    let mut rules: Option<RuleSet> = None;
+    let mut order = PlayOrder::Free;
+    let mut columns = 1;
+    let mut players = vec![];
+    let mut seed = None;
+    let mut games: Vec<Game> = vec![];
+
+    for (number, line) in input.lines().enumerate() {
+        let line = line.trim();
+        let error = |reason: String| ParseError {
+            line: number + 1,
+            reason,
+        };
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with('[') {
+            let (key, value) =
+                parse_header(line).ok_or_else(|| error("malformed header".to_string()))?;
+            match key {
+                "Rules" => {
+                    rules = Some(
+                        RuleSet::by_name(value)
+                            .ok_or_else(|| error(format!("unknown rules '{}'", value)))?,
+                    )
+                }
+                "Order" => {
+                    order = order_by_name(value)
+                        .ok_or_else(|| error(format!("unknown order '{}'", value)))?
+                }
+                "Columns" => {
+                    columns = match value {
+                        "1" => 1,
+                        "3" => 3,
+                        _ => return Err(error(format!("bad columns '{}'", value))),
+                    }
+                }
+                "Player" => players.push(value.to_string()),
+                "Seed" => {
+                    seed = Some(
+                        value
+                            .parse()
+                            .map_err(|_| error(format!("bad seed '{}'", value)))?,
+                    )
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        if games.is_empty() {
+            let rules = rules
+                .as_ref()
+                .ok_or_else(|| error("the Rules header is missing".to_string()))?;
+            let rules = RuleSet {
+                order,
+                ..rules.clone()
+            };
+            games = new_games(&rules, columns, players.len());
+        }
+
+        let colon = line
+            .find(':')
+            .filter(|_| line.starts_with('T'))
+            .ok_or_else(|| error("expected a turn like 'T1: ...'".to_string()))?;
+        let label = &line[1..colon];
+        let player = match label.find(' ') {
+            None => 0,
+            Some(space) => {
+                let name = label[space..].trim();
+                players
+                    .iter()
+                    .position(|p| p == name)
+                    .ok_or_else(|| error(format!("unknown player '{}'", name)))?
+            }
+        };
+
+        let game = &mut games[player];
+        for event in parse_turn(&line[colon + 1..]).map_err(error)? {
+            game.apply(&event).map_err(|e| error(e.to_string()))?;
+
+            if let (Some(Event::Score { points, .. }), Event::Score { points: wrote, .. }) =
+                (game.log().last(), &event)
+            {
+                if points != wrote {
+                    return Err(error(format!("that hand scores {}, not {}", points, wrote)));
+                }
+            }
+        }
+    }
+
+    if games.is_empty() {
+        let rules = rules.ok_or_else(|| ParseError {
+            line: 0,
+            reason: "the Rules header is missing".to_string(),
+        })?;
+        let rules = RuleSet { order, ..rules };
+        games = new_games(&rules, columns, players.len());
+    }
+
+    Ok(Record {
+        players,
+        seed,
+        games,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::LineId as L;
+    use super::super::ScriptedRoller;
+    use super::*;
+
+    fn record(game: Game) -> Record {
+        Record {
+            players: vec!["Ann".to_string()],
+            seed: Some(42),
+            games: vec![game],
+        }
+    }
+
+    #[test]
+    fn writes_a_turn() {
+        let mut game = Game::new(RuleSet::default());
+        game.set_roller(Box::new(
+            ScriptedRoller::new(vec![3, 3, 5, 1, 6, 3, 2, 4]).unwrap(),
+        ));
+        game.roll(&[]).unwrap();
+        game.roll(&[false, false, true, true, true]).unwrap();
+        game.score(L::ThreeKind).unwrap();
+
+        let expected = "[Rules \"modern\"]\n\
+                        [Player \"Ann\"]\n\
+                        [Seed \"42\"]\n\
+                        \n\
+                        T1: 3 3 5 1 6 / keep 33 / 3 3 3 2 4 / 3k=15\n";
+        assert_eq!(write(&record(game)), expected);
+    }
+
+    #[test]
+    fn round_trips_a_game() {
+        let mut game = Game::new_triple(RuleSet {
+            order: PlayOrder::UpperOnly,
+            ..RuleSet::maxi()
+        });
+        game.set_roller(Box::new(
+            ScriptedRoller::new(vec![2, 2, 5, 6, 1, 3, 4]).unwrap(),
+        ));
+        game.roll(&[]).unwrap();
+        game.roll(&[true, false, true, false, false, true]).unwrap();
+        game.roll(&[false; 6]).unwrap();
+        game.score_in(2, L::Ace).unwrap();
+        game.cheat(vec![6; 6]).unwrap();
+        game.score_in(1, L::Dice5).unwrap();
+        game.roll(&[]).unwrap();
+
+        let written = write(&record(game.clone()));
+        let parsed = parse(&written).unwrap();
+        assert_eq!(parsed, record(game));
+    }
+
+    #[test]
+    fn round_trips_several_players() {
+        let mut ann = Game::new(RuleSet::yatzy());
+        let mut bob = Game::new(RuleSet::yatzy());
+        for game in [&mut ann, &mut bob].iter_mut() {
+            game.set_roller(Box::new(ScriptedRoller::new(vec![4, 4, 1, 2, 6]).unwrap()));
+            game.roll(&[]).unwrap();
+            game.score(L::OnePair).unwrap();
+        }
+        ann.roll(&[]).unwrap();
+
+        let record = Record {
+            players: vec!["Ann".to_string(), "Bob".to_string()],
+            seed: None,
+            games: vec![ann, bob],
+        };
+        let written = write(&record);
+        assert!(written.contains("T1 Bob: 4 4 1 2 6 / 1p=8"));
+        assert_eq!(parse(&written).unwrap(), record);
+    }
+
+    #[test]
+    fn ignores_unknown_headers() {
+        let text = "[Rules \"yacht\"]\n[Event \"Club night\"]\n\nT1: 6 6 6 6 6 / d=50\n";
+        let parsed = parse(text).unwrap();
+        assert_eq!(parsed.games[0].grand_total(), 50);
+    }
+
+    #[test]
+    fn rejects_wrong_points() {
+        let text = "[Rules \"modern\"]\nT1: 1 2 3 4 5 / ls=30\n";
+        let err = parse(text).unwrap_err();
+        assert_eq!(err.line, 2);
+        assert_eq!(err.reason, "that hand scores 40, not 30");
+    }
+
+    #[test]
+    fn rejects_dice_that_were_not_kept() {
+        let text = "[Rules \"modern\"]\nT1: 1 2 3 4 5 / keep 66 / 6 6 1 1 1\n";
+        assert_eq!(parse(text).unwrap_err().line, 2);
+    }
+
+    #[test]
+    fn needs_rules() {
+        assert!(parse("T1: 1 2 3 4 5 / c=15\n").is_err());
+    }
+}
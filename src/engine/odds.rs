@@ -0,0 +1,188 @@
+//! Exact odds of making each line before the turn is out.
+//!
+//! The odds come from the transitions between sets of dice rather than from
+//! simulation: the chance for a kept set is the average over the faces the
+//! next die can show, and before each re-roll the best set for the line is
+//! kept.  Every result is a fraction over a power of six.
+
+use super::calchand;
+use super::combos::Combos;
+use super::{Dice, DieFace, GameError, LineId, RuleSet};
+use num_rational::Ratio;
+
+/// An exact probability.
+pub type Odds = Ratio<u128>;
+
+/// The most dice, counting every die of every roll, whose odds fit in
+/// `Odds`: a sum of six chances over `6^48` is still below `u128::MAX`.
+const MAX_DICE_ROLLED: usize = 48;
+
+/// The chance of ending the turn with a hand that scores on `line`, when
+/// `kept` is held, `rolls` rolls are left and every re-roll keeps the best
+/// dice for that line.  With no rolls left `kept` must be the whole hand.
+/// Refused with `TooManyRolls` when the odds would not fit in `Odds`.
+pub fn chance(
+    rules: &RuleSet,
+    line: LineId,
+    kept: &[DieFace],
+    rolls: usize,
+) -> Result<Odds, GameError> {
+    let (combos, kept) = check(rules, kept, rolls)?;
+    Ok(odds_for(rules, &combos, line, kept, rolls))
+}
+
+/// The chance of making every line on the card, in card order.
+pub fn chances(
+    rules: &RuleSet,
+    kept: &[DieFace],
+    rolls: usize,
+) -> Result<Vec<(LineId, Odds)>, GameError> {
+    let (combos, kept) = check(rules, kept, rolls)?;
+    Ok(rules
+        .lines()
+        .into_iter()
+        .map(|line| (line, odds_for(rules, &combos, line, kept, rolls)))
+        .collect())
+}
+
+/// The sets for these rules and the one `kept` makes.
+fn check(rules: &RuleSet, kept: &[DieFace], rolls: usize) -> Result<(Combos, usize), GameError> {
+    if rules.number_of_dice.saturating_mul(rolls) > MAX_DICE_ROLLED {
+        return Err(GameError::TooManyRolls);
+    }
+    let combos = Combos::new(rules.number_of_dice);
+    let kept = combos.index_of_faces(kept)?;
+    Ok((combos, kept))
+}
+
+fn odds_for(rules: &RuleSet, combos: &Combos, line: LineId, kept: usize, rolls: usize) -> Odds {
+    let calc = calchand::get_calc(line, rules.scoring);
+    let mut odds = vec![Odds::from_integer(0); combos.len()];
+    for h in combos.hands() {
+        if calc(&Dice::roll_fake(combos.faces(h)), rules, false) > 0 {
+            odds[h] = Odds::from_integer(1);
+        }
+    }
+
+    for roll in 0..rolls {
+        if roll > 0 {
+            odds = best_of(combos, &odds);
+        }
+        odds = keep_odds(combos, odds);
+    }
+    odds[kept]
+}
+
+/// The chance for each kept set, rolling the rest once.
+fn keep_odds(combos: &Combos, mut odds: Vec<Odds>) -> Vec<Odds> {
+    let faces = Dice::NUMBER_OF_FACES as u128;
+    for size in (0..combos.number_of_dice()).rev() {
+        for k in combos.of_size(size) {
+            let total: Odds = (1..=Dice::NUMBER_OF_FACES)
+                .map(|face| odds[combos.with_die(k, face)])
+                .sum();
+            odds[k] = total / faces;
+        }
+    }
+    odds
+}
+
+/// The chance for each full hand when the best set is kept from it.
+fn best_of(combos: &Combos, odds: &[Odds]) -> Vec<Odds> {
+    let mut best = vec![Odds::from_integer(0); combos.len()];
+    for h in combos.hands() {
+        best[h] = combos
+            .subsets(h)
+            .iter()
+            .map(|&k| odds[k as usize])
+            .max()
+            .unwrap_or(best[h]);
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::LineId as L;
+    use super::*;
+
+    #[test]
+    fn dice5_in_a_full_turn() {
+        let rules = RuleSet::default();
+        assert_eq!(
+            chance(&rules, L::Dice5, &[], 3),
+            Ok(Odds::new(2_783_176, 60_466_176))
+        );
+        assert_eq!(chance(&rules, L::Dice5, &[], 1), Ok(Odds::new(1, 1296)));
+    }
+
+    #[test]
+    fn one_die_short() {
+        let rules = RuleSet::default();
+        assert_eq!(
+            chance(&rules, L::Dice5, &[4, 4, 4, 4], 1),
+            Ok(Odds::new(1, 6))
+        );
+        assert_eq!(
+            chance(&rules, L::Dice5, &[4, 4, 4, 4], 2),
+            Ok(Odds::new(11, 36))
+        );
+        assert_eq!(
+            chance(&rules, L::LargeStraight, &[1, 2, 3, 4], 1),
+            Ok(Odds::new(1, 6))
+        );
+    }
+
+    #[test]
+    fn no_rolls_left_is_certain_either_way() {
+        let rules = RuleSet::default();
+        assert_eq!(
+            chance(&rules, L::Dice5, &[3, 3, 3, 3, 3], 0),
+            Ok(Odds::from_integer(1))
+        );
+        assert_eq!(
+            chance(&rules, L::Dice5, &[3, 3, 3, 3, 2], 0),
+            Ok(Odds::from_integer(0))
+        );
+    }
+
+    #[test]
+    fn covers_every_line() {
+        let rules = RuleSet::default();
+        let odds = chances(&rules, &[], 3).unwrap();
+
+        assert_eq!(odds.len(), rules.lines().len());
+        assert!(odds.contains(&(L::Chance, Odds::from_integer(1))));
+    }
+
+    #[test]
+    fn saved_rolls_in_maxi() {
+        let rules = RuleSet::maxi();
+        let dice6 = chance(&rules, L::Dice5, &[], 7).unwrap();
+        assert!(dice6 > chance(&rules, L::Dice5, &[], 6).unwrap());
+        assert!(dice6 < Odds::from_integer(1));
+
+        assert!(chance(&rules, L::Dice5, &[], 8).is_ok());
+        assert_eq!(
+            chance(&rules, L::Dice5, &[], 9),
+            Err(GameError::TooManyRolls)
+        );
+        assert_eq!(
+            chances(&rules, &[], usize::MAX),
+            Err(GameError::TooManyRolls)
+        );
+    }
+
+    #[test]
+    fn rejects_impossible_dice() {
+        let rules = RuleSet::default();
+        assert_eq!(
+            chance(&rules, L::Chance, &[1, 2, 3, 4, 5, 6], 1),
+            Err(GameError::TooManyDice)
+        );
+        assert_eq!(
+            chance(&rules, L::Chance, &[7], 1),
+            Err(GameError::InvalidDieFace)
+        );
+    }
+}
@@ -0,0 +1,246 @@
+use super::{Dice, DieFace, GameError};
+use rand::Rng;
+use std::fmt;
+
+/// Where the dice come from.  Every roll in the engine goes through one of
+/// these, so a game can be replayed or scripted.
+pub trait DiceRoller: fmt::Debug + Send {
+    /// A single die, from 1 to `Dice::NUMBER_OF_FACES`.
+    fn roll_die(&mut self) -> DieFace;
+
+    /// Told before every roll: the turn, counting from 0, and the roll of
+    /// the turn, counting from 1.  Rollers that deal from a stream ignore it.
+    fn start_roll(&mut self, _turn: usize, _roll: usize) {}
+
+    /// The die at `position` in the hand, for the roll last started.
+    fn roll_die_at(&mut self, _position: usize) -> DieFace {
+        self.roll_die()
+    }
+
+    fn box_clone(&self) -> Box<dyn DiceRoller>;
+}
+
+impl Clone for Box<dyn DiceRoller> {
+    fn clone(&self) -> Self {
+        self.box_clone()
+    }
+}
+
+/// Fresh randomness for every roll, seeded from the operating system.
+#[derive(Clone, Default, Debug)]
+pub struct OsRoller;
+
+impl DiceRoller for OsRoller {
+    fn roll_die(&mut self) -> DieFace {
+        rand::thread_rng().gen_range(0, Dice::NUMBER_OF_FACES) + 1
+    }
+
+    fn box_clone(&self) -> Box<dyn DiceRoller> {
+        Box::new(self.clone())
+    }
+}
+
+const GOLDEN_GAMMA: u64 = 0x9E37_79B9_7F4A_7C15;
+
+/// The SplitMix64 output function.
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn face_of(bits: u64) -> DieFace {
+    (bits % Dice::NUMBER_OF_FACES as u64) as DieFace + 1
+}
+
+/// A SplitMix64 generator.  It is written out here rather than taken from
+/// `rand`, so the same seed rolls the same dice on every platform and with
+/// every version of our dependencies.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SeededRoller {
+    state: u64,
+}
+
+impl SeededRoller {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(GOLDEN_GAMMA);
+        mix(self.state)
+    }
+}
+
+impl DiceRoller for SeededRoller {
+    fn roll_die(&mut self) -> DieFace {
+        face_of(self.next_u64())
+    }
+
+    fn box_clone(&self) -> Box<dyn DiceRoller> {
+        Box::new(self.clone())
+    }
+}
+
+/// Every die is worked out from the key and where it is rolled: the turn,
+/// the roll of the turn and its position in the hand.  Two games with the
+/// same key deal the same dice to the same places however they are played,
+/// so a die one player re-rolls is the die another re-rolling it would get.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct KeyedRoller {
+    key: u64,
+    turn: u64,
+    roll: u64,
+    /// Dice dealt this roll without a position.
+    dealt: usize,
+}
+
+impl KeyedRoller {
+    pub fn new(key: u64) -> Self {
+        Self {
+            key,
+            turn: 0,
+            roll: 0,
+            dealt: 0,
+        }
+    }
+}
+
+impl DiceRoller for KeyedRoller {
+    /// Without a position the dice are dealt left to right.
+    fn roll_die(&mut self) -> DieFace {
+        self.dealt += 1;
+        self.roll_die_at(self.dealt - 1)
+    }
+
+    fn start_roll(&mut self, turn: usize, roll: usize) {
+        self.turn = turn as u64;
+        self.roll = roll as u64;
+        self.dealt = 0;
+    }
+
+    fn roll_die_at(&mut self, position: usize) -> DieFace {
+        let bits = [self.turn, self.roll, position as u64]
+            .iter()
+            .fold(mix(self.key), |z, &part| {
+                mix(z.wrapping_add(GOLDEN_GAMMA) ^ part)
+            });
+        face_of(bits)
+    }
+
+    fn box_clone(&self) -> Box<dyn DiceRoller> {
+        Box::new(self.clone())
+    }
+}
+
+/// Rolls the given faces in order, starting over at the end of the script.
+/// An empty script rolls nothing but ones.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ScriptedRoller {
+    faces: Vec<DieFace>,
+    next: usize,
+}
+
+impl ScriptedRoller {
+    /// Refuses a script with a face that is not on a die.
+    pub fn new(faces: Vec<DieFace>) -> Result<Self, GameError> {
+        if faces
+            .iter()
+            .any(|f| !(1..=Dice::NUMBER_OF_FACES).contains(f))
+        {
+            return Err(GameError::InvalidDieFace);
+        }
+        Ok(Self { faces, next: 0 })
+    }
+}
+
+impl DiceRoller for ScriptedRoller {
+    fn roll_die(&mut self) -> DieFace {
+        if self.faces.is_empty() {
+            return 1;
+        }
+        let face = self.faces[self.next % self.faces.len()];
+        self.next += 1;
+        face
+    }
+
+    fn box_clone(&self) -> Box<dyn DiceRoller> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_dice() {
+        let mut a = SeededRoller::new(42);
+        let mut b = SeededRoller::new(42);
+        let rolls_a: Vec<_> = (0..50).map(|_| a.roll_die()).collect();
+        let rolls_b: Vec<_> = (0..50).map(|_| b.roll_die()).collect();
+
+        assert_eq!(rolls_a, rolls_b);
+        assert!(rolls_a.iter().all(|f| (1..=6).contains(f)));
+    }
+
+    #[test]
+    fn seeded_roller_is_portable() {
+        // Pinned so a change to the generator is caught: saved seeds in bug
+        // reports must keep rolling the same dice.
+        let mut roller = SeededRoller::new(1);
+        let rolls: Vec<_> = (0..10).map(|_| roller.roll_die()).collect();
+        assert_eq!(rolls, vec![6, 2, 1, 6, 4, 3, 4, 4, 1, 5]);
+    }
+
+    #[test]
+    fn different_seeds_differ() {
+        let mut a = SeededRoller::new(1);
+        let mut b = SeededRoller::new(2);
+        let rolls_a: Vec<_> = (0..20).map(|_| a.roll_die()).collect();
+        let rolls_b: Vec<_> = (0..20).map(|_| b.roll_die()).collect();
+
+        assert_ne!(rolls_a, rolls_b);
+    }
+
+    #[test]
+    fn keyed_roller_depends_only_on_where() {
+        let mut a = KeyedRoller::new(7);
+        let mut b = KeyedRoller::new(7);
+        a.start_roll(3, 2);
+        let forward: Vec<_> = (0..5).map(|p| a.roll_die_at(p)).collect();
+        b.start_roll(3, 2);
+        let backward: Vec<_> = (0..5).rev().map(|p| b.roll_die_at(p)).collect();
+        assert_eq!(forward, backward.into_iter().rev().collect::<Vec<_>>());
+
+        b.start_roll(3, 2);
+        assert_eq!((0..5).map(|_| b.roll_die()).collect::<Vec<_>>(), forward);
+        assert!(forward.iter().all(|f| (1..=6).contains(f)));
+
+        let turns: Vec<_> = (0..20)
+            .map(|turn| {
+                a.start_roll(turn, 1);
+                a.roll_die_at(0)
+            })
+            .collect();
+        assert!(turns.iter().any(|&f| f != turns[0]));
+    }
+
+    #[test]
+    fn scripted_roller_repeats_its_script() {
+        let mut roller = ScriptedRoller::new(vec![1, 2, 3]).unwrap();
+        let rolls: Vec<_> = (0..5).map(|_| roller.roll_die()).collect();
+
+        assert_eq!(rolls, vec![1, 2, 3, 1, 2]);
+        assert_eq!(ScriptedRoller::new(vec![]).unwrap().roll_die(), 1);
+    }
+
+    #[test]
+    fn scripted_roller_refuses_faces_off_the_die() {
+        assert_eq!(
+            ScriptedRoller::new(vec![1, 7]),
+            Err(GameError::InvalidDieFace)
+        );
+        assert_eq!(ScriptedRoller::new(vec![0]), Err(GameError::InvalidDieFace));
+    }
+}
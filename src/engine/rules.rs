@@ -0,0 +1,304 @@
+use super::LineId;
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum JokerRule {
+    /// A second Dice5 is scored like any other hand.
+    None,
+    /// A bonus Dice5 may be played on any open line.
+    Free,
+    /// A bonus Dice5 must follow the printed placement order.
+    Forced,
+}
+
+/// Which family of categories the card uses and how they are scored.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Scoring {
+    /// 3 and 4 of a kind score all dice, straights and full house are fixed.
+    Dice5,
+    /// Scandinavian rules: pairs and kinds score only the matched dice.
+    Yatzy,
+    /// Yatzy scoring with six dice and the extra Maxi categories.
+    Maxi,
+    /// The historical Yacht card: no 3 of a kind and no upper bonus.
+    Yacht,
+    /// Latin American Generala: fixed values with a bonus when served.
+    Generala,
+}
+
+/// Whether lines must be filled in the order they are printed.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum PlayOrder {
+    Free,
+    Strict,
+    /// Only the upper section must be filled top to bottom.
+    UpperOnly,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct RuleSet {
+    pub name: String,
+    pub number_of_dice: usize,
+    pub small_straight: i16,
+    pub large_straight: i16,
+    pub full_straight: i16,
+    pub full_house: i16,
+    pub four_kind: i16,
+    pub dice5: i16,
+    pub double_dice5: i16,
+    pub upper_bonus_threshold: i16,
+    pub upper_bonus: i16,
+    pub dice5_bonus: i16,
+    pub joker: JokerRule,
+    pub scoring: Scoring,
+    /// Unused rolls carry over to the player's later turns.
+    pub save_rolls: bool,
+    /// Extra points for a category made on the first roll of the turn.
+    pub served_bonus: i16,
+    /// A Dice5 made on the first roll of the turn wins the game outright.
+    pub served_dice5_wins: bool,
+    pub order: PlayOrder,
+}
+
+impl RuleSet {
+    pub const PRESETS: [&'static str; 7] = [
+        "1967", "modern", "house", "yatzy", "maxi", "yacht", "generala",
+    ];
+
+    pub fn classic() -> Self {
+        Self {
+            name: "1967".to_string(),
+            number_of_dice: 5,
+            small_straight: 30,
+            large_straight: 40,
+            full_straight: 0,
+            full_house: 25,
+            four_kind: 0,
+            dice5: 50,
+            double_dice5: 0,
+            upper_bonus_threshold: 63,
+            upper_bonus: 35,
+            dice5_bonus: 100,
+            joker: JokerRule::Free,
+            scoring: Scoring::Dice5,
+            save_rolls: false,
+            served_bonus: 0,
+            served_dice5_wins: false,
+            order: PlayOrder::Free,
+        }
+    }
+
+    pub fn modern() -> Self {
+        Self {
+            name: "modern".to_string(),
+            joker: JokerRule::Forced,
+            ..Self::classic()
+        }
+    }
+
+    pub fn house() -> Self {
+        Self {
+            name: "house".to_string(),
+            full_house: 40,
+            upper_bonus: 50,
+            ..Self::modern()
+        }
+    }
+
+    pub fn yatzy() -> Self {
+        Self {
+            name: "yatzy".to_string(),
+            number_of_dice: 5,
+            small_straight: 15,
+            large_straight: 20,
+            full_straight: 0,
+            full_house: 0,
+            four_kind: 0,
+            dice5: 50,
+            double_dice5: 0,
+            upper_bonus_threshold: 63,
+            upper_bonus: 50,
+            dice5_bonus: 0,
+            joker: JokerRule::None,
+            scoring: Scoring::Yatzy,
+            save_rolls: false,
+            served_bonus: 0,
+            served_dice5_wins: false,
+            order: PlayOrder::Free,
+        }
+    }
+
+    pub fn maxi() -> Self {
+        Self {
+            name: "maxi".to_string(),
+            number_of_dice: 6,
+            full_straight: 21,
+            dice5: 100,
+            upper_bonus_threshold: 84,
+            scoring: Scoring::Maxi,
+            save_rolls: true,
+            ..Self::yatzy()
+        }
+    }
+
+    pub fn yacht() -> Self {
+        Self {
+            name: "yacht".to_string(),
+            small_straight: 30,
+            large_straight: 30,
+            upper_bonus: 0,
+            scoring: Scoring::Yacht,
+            ..Self::yatzy()
+        }
+    }
+
+    pub fn generala() -> Self {
+        Self {
+            name: "generala".to_string(),
+            large_straight: 20,
+            full_house: 30,
+            four_kind: 40,
+            dice5: 50,
+            double_dice5: 100,
+            upper_bonus: 0,
+            scoring: Scoring::Generala,
+            served_bonus: 5,
+            served_dice5_wins: true,
+            ..Self::yatzy()
+        }
+    }
+
+    pub fn by_name(name: &str) -> Option<Self> {
+        match name {
+            "1967" => Some(Self::classic()),
+            "modern" => Some(Self::modern()),
+            "house" => Some(Self::house()),
+            "yatzy" => Some(Self::yatzy()),
+            "maxi" => Some(Self::maxi()),
+            "yacht" => Some(Self::yacht()),
+            "generala" => Some(Self::generala()),
+            _ => None,
+        }
+    }
+
+    /// The scoring lines printed on the card, top to bottom.
+    pub fn lines(&self) -> Vec<LineId> {
+        let mut lines = vec![
+            LineId::Ace,
+            LineId::Two,
+            LineId::Three,
+            LineId::Four,
+            LineId::Five,
+            LineId::Six,
+        ];
+
+        match self.scoring {
+            Scoring::Dice5 => lines.extend_from_slice(&[
+                LineId::ThreeKind,
+                LineId::FourKind,
+                LineId::SmallStraight,
+                LineId::LargeStraight,
+                LineId::FullHouse,
+            ]),
+            Scoring::Yatzy => lines.extend_from_slice(&[
+                LineId::OnePair,
+                LineId::TwoPairs,
+                LineId::ThreeKind,
+                LineId::FourKind,
+                LineId::SmallStraight,
+                LineId::LargeStraight,
+                LineId::FullHouse,
+            ]),
+            Scoring::Maxi => lines.extend_from_slice(&[
+                LineId::OnePair,
+                LineId::TwoPairs,
+                LineId::ThreePairs,
+                LineId::ThreeKind,
+                LineId::FourKind,
+                LineId::SmallStraight,
+                LineId::LargeStraight,
+                LineId::FullStraight,
+                LineId::FullHouse,
+                LineId::Castle,
+                LineId::Tower,
+            ]),
+            Scoring::Yacht => lines.extend_from_slice(&[
+                LineId::FullHouse,
+                LineId::FourKind,
+                LineId::SmallStraight,
+                LineId::LargeStraight,
+            ]),
+            Scoring::Generala => lines.extend_from_slice(&[
+                LineId::LargeStraight,
+                LineId::FullHouse,
+                LineId::FourKind,
+                LineId::Dice5,
+                LineId::DoubleDice5,
+            ]),
+        }
+
+        if self.scoring != Scoring::Generala {
+            lines.extend_from_slice(&[LineId::Chance, LineId::Dice5]);
+        }
+        lines
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self::modern()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn by_name_finds_every_preset() {
+        for name in RuleSet::PRESETS.iter() {
+            let rules = RuleSet::by_name(name).expect("preset missing");
+            assert_eq!(&rules.name, name);
+        }
+    }
+
+    #[test]
+    fn by_name_unknown() {
+        assert_eq!(RuleSet::by_name("bogus"), None);
+    }
+
+    #[test]
+    fn house_overrides_full_house_and_bonus() {
+        let rules = RuleSet::house();
+        assert_eq!(rules.full_house, 40);
+        assert_eq!(rules.upper_bonus, 50);
+        assert_eq!(rules.upper_bonus_threshold, 63);
+    }
+
+    #[test]
+    fn yatzy_card_has_pair_lines() {
+        let lines = RuleSet::yatzy().lines();
+        assert_eq!(lines.len(), 15);
+        assert!(lines.contains(&LineId::OnePair));
+        assert!(lines.contains(&LineId::TwoPairs));
+        assert!(!RuleSet::modern().lines().contains(&LineId::OnePair));
+    }
+
+    #[test]
+    fn maxi_card_uses_six_dice_and_saved_rolls() {
+        let rules = RuleSet::maxi();
+        assert_eq!(rules.number_of_dice, 6);
+        assert!(rules.save_rolls);
+        assert_eq!(rules.lines().len(), 19);
+        assert!(rules.lines().contains(&LineId::Castle));
+    }
+
+    #[test]
+    fn yacht_and_generala_have_no_upper_bonus() {
+        assert_eq!(RuleSet::yacht().upper_bonus, 0);
+        assert_eq!(RuleSet::generala().upper_bonus, 0);
+        assert_eq!(RuleSet::yacht().lines().len(), 12);
+        assert_eq!(RuleSet::generala().lines().len(), 11);
+        assert!(!RuleSet::generala().lines().contains(&LineId::Chance));
+    }
+}
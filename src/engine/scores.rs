@@ -0,0 +1,315 @@
+//! A record of finished games, kept as one JSON entry per line, and the
+//! high-score tables and statistics drawn from it.  Games where the dice
+//! were set by hand are recorded but left out of both.
+
+use super::{Event, Game, LineId};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// One player's finished game.
+#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Entry {
+    pub player: String,
+    /// When the game finished, in seconds since the Unix epoch.
+    pub finished: u64,
+    pub rules: String,
+    pub total: i16,
+    /// Won outright with a served Dice5, whatever the total.
+    pub won: bool,
+    /// Every column's points line by line, in card order, followed by the
+    /// upper bonus and the Dice5 bonus.
+    pub columns: Vec<Vec<(LineId, i16)>>,
+    pub bonus_dice5s: u16,
+    /// The dice were set by hand at some point.
+    #[serde(default)]
+    pub cheated: bool,
+}
+
+impl Entry {
+    pub fn new(player: &str, finished: u64, game: &Game) -> Self {
+        let columns = game
+            .card()
+            .iter()
+            .map(|column| {
+                let lines = column
+                    .line
+                    .iter()
+                    .map(|line| (line.id, line.value.unwrap_or(0)));
+                let bonuses = [LineId::UpperBonus, LineId::Dice5Bonus]
+                    .iter()
+                    .map(|&id| (id, column.subtotal(id).unwrap_or(0)));
+                lines.chain(bonuses).collect()
+            })
+            .collect();
+
+        Self {
+            player: player.to_string(),
+            finished,
+            rules: game.rules().name.clone(),
+            total: game.grand_total(),
+            won: game.card().iter().any(|c| c.won),
+            columns,
+            bonus_dice5s: game
+                .card()
+                .iter()
+                .map(|c| c.bonus_dice5.max(0) as u16)
+                .sum(),
+            cheated: game
+                .log()
+                .iter()
+                .any(|event| matches!(event, Event::SetDice { .. })),
+        }
+    }
+
+    fn scored(&self, id: LineId) -> bool {
+        self.columns
+            .iter()
+            .flatten()
+            .any(|&(line, points)| line == id && points > 0)
+    }
+
+    /// Made the upper bonus in any column.
+    pub fn upper_bonus(&self) -> bool {
+        self.scored(LineId::UpperBonus)
+    }
+
+    /// Scored the Dice5 line in any column.
+    pub fn dice5(&self) -> bool {
+        self.scored(LineId::Dice5)
+    }
+}
+
+/// Adds an entry to the end of the file, creating it if need be.
+pub fn append(path: &Path, entry: &Entry) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let line = serde_json::to_string(entry)?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)
+}
+
+/// Every entry in the file, oldest first.  A missing file has none, and
+/// lines that are not entries, such as one cut short by a crash, are
+/// skipped.
+pub fn load(path: &Path) -> io::Result<Vec<Entry>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err),
+    };
+    Ok(text
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// The best `n` fair games, outright wins first.  Ties go to the earlier
+/// game.
+pub fn top<'a>(entries: &[&'a Entry], n: usize) -> Vec<&'a Entry> {
+    let mut best: Vec<_> = entries.iter().copied().filter(|e| !e.cheated).collect();
+    best.sort_by_key(|e| (!e.won, -e.total, e.finished));
+    best.truncate(n);
+    best
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Stats {
+    pub games: usize,
+    pub mean: f64,
+    pub best: i16,
+    pub worst: i16,
+    /// The share of games that made the upper bonus.
+    pub upper_bonus_rate: f64,
+    /// The share of games that scored the Dice5 line.
+    pub dice5_rate: f64,
+    /// How many games had no bonus Dice5, how many had one, two and so on.
+    pub bonus_dice5s: Vec<usize>,
+    /// The average points on each line per column, in the order the lines
+    /// were first seen.
+    pub lines: Vec<(LineId, f64)>,
+}
+
+/// Statistics over the fair games in `entries`, or `None` when there are
+/// none.
+pub fn stats(entries: &[&Entry]) -> Option<Stats> {
+    let entries: Vec<_> = entries.iter().copied().filter(|e| !e.cheated).collect();
+    let games = entries.len();
+    if games == 0 {
+        return None;
+    }
+    let share = |count: usize| count as f64 / games as f64;
+
+    let mut bonus_dice5s = vec![];
+    for entry in &entries {
+        let bonuses = usize::from(entry.bonus_dice5s);
+        if bonus_dice5s.len() <= bonuses {
+            bonus_dice5s.resize(bonuses + 1, 0);
+        }
+        bonus_dice5s[bonuses] += 1;
+    }
+
+    let mut lines: Vec<(LineId, i64, usize)> = vec![];
+    for &(id, points) in entries.iter().flat_map(|e| e.columns.iter().flatten()) {
+        match lines.iter_mut().find(|(line, _, _)| *line == id) {
+            Some((_, total, count)) => {
+                *total += i64::from(points);
+                *count += 1;
+            }
+            None => lines.push((id, i64::from(points), 1)),
+        }
+    }
+
+    Some(Stats {
+        games,
+        mean: entries.iter().map(|e| f64::from(e.total)).sum::<f64>() / games as f64,
+        best: entries.iter().map(|e| e.total).max().unwrap_or(0),
+        worst: entries.iter().map(|e| e.total).min().unwrap_or(0),
+        upper_bonus_rate: share(entries.iter().filter(|e| e.upper_bonus()).count()),
+        dice5_rate: share(entries.iter().filter(|e| e.dice5()).count()),
+        bonus_dice5s,
+        lines: lines
+            .into_iter()
+            .map(|(id, total, count)| (id, total as f64 / count as f64))
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{DieFace, RuleSet, ScriptedRoller};
+    use super::*;
+
+    fn score(game: &mut Game, faces: Vec<DieFace>, line: LineId) {
+        game.roll(&[]).unwrap();
+        game.cheat(faces).unwrap();
+        game.score(line).unwrap();
+    }
+
+    /// A finished game: Dice5s on `opening`, then no luck at all.
+    fn finished(opening: &[(DieFace, LineId)]) -> Game {
+        let mut game = Game::new(RuleSet::default());
+        for &(face, line) in opening {
+            score(&mut game, vec![face; 5], line);
+        }
+        for line in RuleSet::default().lines() {
+            if game
+                .scorecard()
+                .get_line_by_id(line)
+                .unwrap()
+                .value
+                .is_none()
+            {
+                score(&mut game, vec![1, 2, 3, 5, 5], line);
+            }
+        }
+        game
+    }
+
+    fn entry(player: &str, finished: u64, total: i16) -> Entry {
+        Entry {
+            player: player.to_string(),
+            finished,
+            rules: "modern".to_string(),
+            total,
+            won: false,
+            columns: vec![vec![(LineId::Chance, total)]],
+            bonus_dice5s: 0,
+            cheated: false,
+        }
+    }
+
+    #[test]
+    fn an_entry_breaks_the_game_down() {
+        let game = finished(&[
+            (6, LineId::Dice5),
+            (6, LineId::Six),
+            (5, LineId::Five),
+            (4, LineId::Four),
+        ]);
+        let entry = Entry::new("Ann", 7, &game);
+
+        assert_eq!(entry.total, game.grand_total());
+        assert_eq!(entry.rules, "modern");
+        assert!(entry.upper_bonus());
+        assert!(entry.dice5());
+        assert_eq!(entry.bonus_dice5s, 3);
+        assert_eq!(entry.columns[0][0], (LineId::Ace, 1));
+        assert_eq!(entry.columns[0][5], (LineId::Six, 30));
+        assert!(entry.cheated);
+
+        let plain = Entry::new("Bob", 7, &finished(&[]));
+        assert!(!plain.upper_bonus());
+        assert!(!plain.dice5());
+        assert_eq!(plain.bonus_dice5s, 0);
+    }
+
+    #[test]
+    fn cheated_games_are_left_out() {
+        let cheated = Entry::new("Ann", 1, &finished(&[]));
+        let mut game = Game::new(RuleSet::default());
+        game.set_roller(Box::new(ScriptedRoller::new(vec![2, 3, 4, 5, 6]).unwrap()));
+        for line in RuleSet::default().lines() {
+            game.roll(&[]).unwrap();
+            game.score(line).unwrap();
+        }
+        let fair = Entry::new("Bob", 2, &game);
+        assert!(!fair.cheated);
+
+        let all = [&cheated, &fair];
+        assert_eq!(top(&all, 10), vec![&fair]);
+        assert_eq!(stats(&all).unwrap().games, 1);
+        assert_eq!(stats(&[&cheated]), None);
+    }
+
+    #[test]
+    fn top_puts_wins_and_early_games_first() {
+        let mut won = entry("Cy", 5, 10);
+        won.won = true;
+        let entries = [entry("Ann", 2, 200), entry("Bob", 1, 200), won];
+        let all: Vec<_> = entries.iter().collect();
+
+        let names: Vec<_> = top(&all, 2).iter().map(|e| e.player.as_str()).collect();
+        assert_eq!(names, ["Cy", "Bob"]);
+    }
+
+    #[test]
+    fn stats_over_entries() {
+        let mut lucky = entry("Ann", 1, 300);
+        lucky.bonus_dice5s = 2;
+        let entries = [entry("Ann", 2, 100), entry("Ann", 3, 200), lucky];
+        let all: Vec<_> = entries.iter().collect();
+
+        let stats = stats(&all).unwrap();
+        assert_eq!(stats.games, 3);
+        assert_eq!(stats.mean, 200.0);
+        assert_eq!((stats.best, stats.worst), (300, 100));
+        assert_eq!(stats.bonus_dice5s, vec![2, 0, 1]);
+        assert_eq!(stats.lines, vec![(LineId::Chance, 200.0)]);
+        assert_eq!(stats.upper_bonus_rate, 0.0);
+        assert_eq!(super::stats(&[]), None);
+    }
+
+    #[test]
+    fn entries_survive_a_round_trip_to_disk() {
+        let path = std::env::temp_dir().join(format!("dice5-scores-{}.jsonl", std::process::id()));
+        fs::remove_file(&path).ok();
+        assert_eq!(load(&path).unwrap(), vec![]);
+
+        let entries = vec![entry("Ann", 1, 120), entry("Bob", 2, 240)];
+        for entry in &entries {
+            append(&path, entry).unwrap();
+        }
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{\"player\":\"Cy\",")
+            .unwrap();
+
+        assert_eq!(load(&path).unwrap(), entries);
+        fs::remove_file(path).unwrap();
+    }
+}
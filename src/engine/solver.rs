@@ -0,0 +1,507 @@
+//! The strategy that maximizes the expected final score of a solitaire
+//! game.
+//!
+//! A position between turns is summed up by which lines are filled, the
+//! upper subtotal (capped at the bonus threshold, since points past it make
+//! no difference) and whether the Dice5 box holds points, a zero or nothing.
+//! The solver works out the expected score still to come from every such
+//! position, filling its table on demand, and from that the best keep and
+//! the best line for any hand.
+
+use super::combos::Combos;
+use super::{get_new_scorecard_data, Dice, GameError, LineId, RuleSet, ScoreCardData};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// What is in the Dice5 box, as far as the Joker rules care.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Dice5Box {
+    Open,
+    Zero,
+    Scored,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+struct State {
+    filled: u32,
+    upper: u8,
+    dice5: Dice5Box,
+}
+
+impl State {
+    fn key(self) -> u64 {
+        u64::from(self.filled) << 10 | u64::from(self.upper) << 2 | self.dice5 as u64
+    }
+}
+
+/// The best dice to keep before a re-roll.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Keep {
+    /// The re-roll mask for `Game::roll`: `true` for the dice to roll again.
+    pub mask: Vec<bool>,
+    /// The expected final score when playing on perfectly.
+    pub expected: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    rules: RuleSet,
+    table: HashMap<u64, f32>,
+}
+
+pub struct Solver {
+    rules: RuleSet,
+    lines: Vec<LineId>,
+    upper_cap: u8,
+    combos: Combos,
+    table: HashMap<u64, f32>,
+}
+
+impl Solver {
+    /// A solver with an empty table.  Rule sets where turns are not
+    /// independent, such as saved rolls or served bonuses, are not solved.
+    pub fn new(rules: &RuleSet) -> Result<Self, GameError> {
+        let lines = rules.lines();
+        if rules.save_rolls
+            || rules.served_bonus != 0
+            || rules.served_dice5_wins
+            || lines.len() > 22
+        {
+            return Err(GameError::UnsupportedRules);
+        }
+
+        let upper_cap = if rules.upper_bonus > 0 {
+            rules.upper_bonus_threshold.clamp(0, 255) as u8
+        } else {
+            0
+        };
+
+        Ok(Self {
+            rules: rules.clone(),
+            lines,
+            upper_cap,
+            combos: Combos::new(rules.number_of_dice),
+            table: HashMap::new(),
+        })
+    }
+
+    /// Loads a table saved by `save`, or starts afresh if there is none or
+    /// it was made for other rules.
+    pub fn load(path: &Path, rules: &RuleSet) -> Result<Self, GameError> {
+        let mut solver = Self::new(rules)?;
+
+        let cached = fs::read(path)
+            .ok()
+            .and_then(|bytes| bincode::deserialize::<CacheFile>(&bytes).ok());
+        if let Some(cache) = cached {
+            if cache.rules == *rules {
+                solver.table = cache.table;
+            }
+        }
+        Ok(solver)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let cache = CacheFile {
+            rules: self.rules.clone(),
+            table: self.table.clone(),
+        };
+        let bytes = bincode::serialize(&cache).map_err(io::Error::other)?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, bytes)
+    }
+
+    pub fn rules(&self) -> &RuleSet {
+        &self.rules
+    }
+
+    /// How many positions have been solved so far.
+    pub fn table_len(&self) -> usize {
+        self.table.len()
+    }
+
+    /// Solves every position of the game, which takes a while.
+    pub fn solve_all(&mut self) -> f64 {
+        let start = State {
+            filled: 0,
+            upper: 0,
+            dice5: Dice5Box::Open,
+        };
+        self.value(start)
+    }
+
+    /// The expected final score of a card, between turns, with perfect play.
+    pub fn expected_score(&mut self, card: &ScoreCardData) -> Result<f64, GameError> {
+        let state = self.state_of(card)?;
+        Ok(self.banked(card) + self.value(state))
+    }
+
+    /// The keep that gives the best expected final score for this hand, or
+    /// `None` when no rolls are left.
+    pub fn best_keep(
+        &mut self,
+        card: &ScoreCardData,
+        hand: &Dice,
+    ) -> Result<Option<Keep>, GameError> {
+        let hand_index = self.check_hand(hand)?;
+        if hand.rolls_left <= 0 {
+            return Ok(None);
+        }
+        let state = self.state_of(card)?;
+        let rolls = hand.rolls_left as usize;
+
+        let values = self.hand_values(state);
+        let keeps = self.combos.keep_values_with(values, rolls);
+
+        let best = self
+            .combos
+            .subsets(hand_index)
+            .iter()
+            .map(|&k| k as usize)
+            .fold(None, |best: Option<(usize, f64)>, k| match best {
+                Some((_, value)) if value >= keeps[k] => best,
+                _ => Some((k, keeps[k])),
+            });
+
+        Ok(best.map(|(k, value)| {
+            let mut kept = *self.combos.counts(k);
+            let mask = hand
+                .dice
+                .iter()
+                .map(|&face| {
+                    let count = &mut kept[face as usize - 1];
+                    if *count > 0 {
+                        *count -= 1;
+                        false
+                    } else {
+                        true
+                    }
+                })
+                .collect();
+            Keep {
+                mask,
+                expected: self.banked(card) + value,
+            }
+        }))
+    }
+
+    /// The line to play this hand on for the best expected final score,
+    /// with that score.
+    pub fn best_line(
+        &mut self,
+        card: &ScoreCardData,
+        hand: &Dice,
+    ) -> Result<Option<(LineId, f64)>, GameError> {
+        self.check_hand(hand)?;
+        let state = self.state_of(card)?;
+        let banked = self.banked(card);
+
+        Ok(self
+            .choices(state, card, hand)
+            .into_iter()
+            .fold(
+                None,
+                |best: Option<(LineId, f64)>, (id, value)| match best {
+                    Some((_, v)) if v >= value => best,
+                    _ => Some((id, value)),
+                },
+            )
+            .map(|(id, value)| (id, banked + value)))
+    }
+
+    /// The hand's set, if it is a full hand of real dice.
+    fn check_hand(&self, hand: &Dice) -> Result<usize, GameError> {
+        if hand.dice.len() != self.combos.number_of_dice() {
+            return Err(GameError::DiceCount);
+        }
+        self.combos.index_of_faces(&hand.dice)
+    }
+
+    fn state_of(&self, card: &ScoreCardData) -> Result<State, GameError> {
+        if card.rules != self.rules {
+            return Err(GameError::UnsupportedRules);
+        }
+
+        let mut filled = 0;
+        let mut upper: i16 = 0;
+        for (i, line) in card.line.iter().enumerate() {
+            if let Some(value) = line.value {
+                filled |= 1 << i;
+                if line.id.is_upper() {
+                    upper += value;
+                }
+            }
+        }
+
+        let dice5 = match card
+            .get_line_by_id(LineId::Dice5)
+            .ok()
+            .and_then(|l| l.value)
+        {
+            None => Dice5Box::Open,
+            Some(0) => Dice5Box::Zero,
+            Some(_) => Dice5Box::Scored,
+        };
+
+        Ok(State {
+            filled,
+            upper: upper.max(0).min(i16::from(self.upper_cap)) as u8,
+            dice5,
+        })
+    }
+
+    /// Points already on the card, not counting the upper bonus, which the
+    /// table adds at the end of the game.
+    fn banked(&self, card: &ScoreCardData) -> f64 {
+        let lines: i16 = card.line.iter().flat_map(|l| l.value).sum();
+        f64::from(lines + i16::from(card.bonus_dice5) * self.rules.dice5_bonus)
+    }
+
+    /// A card in this position.  Only which lines are filled matters to the
+    /// rules, so filled lines hold zero, except a Dice5 that scored.
+    fn card_for(&self, state: State) -> ScoreCardData {
+        let mut card = get_new_scorecard_data(self.rules.clone());
+        for (i, line) in card.line.iter_mut().enumerate() {
+            if state.filled & 1 << i != 0 {
+                line.value = Some(match (line.id, state.dice5) {
+                    (LineId::Dice5, Dice5Box::Scored) => self.rules.dice5,
+                    _ => 0,
+                });
+            }
+        }
+        card
+    }
+
+    fn child(&self, state: State, line: usize, points: i16) -> State {
+        let id = self.lines[line];
+        let mut next = State {
+            filled: state.filled | 1 << line,
+            ..state
+        };
+
+        if id.is_upper() {
+            let upper = i16::from(state.upper) + points;
+            next.upper = upper.min(i16::from(self.upper_cap)) as u8;
+        }
+        if id == LineId::Dice5 {
+            next.dice5 = if points > 0 {
+                Dice5Box::Scored
+            } else {
+                Dice5Box::Zero
+            };
+        }
+        next
+    }
+
+    /// Every line the hand may be played on, with the expected points from
+    /// here to the end of the game if it is.
+    fn choices(&mut self, state: State, card: &ScoreCardData, hand: &Dice) -> Vec<(LineId, f64)> {
+        let bonus = if card.is_joker(hand) && state.dice5 == Dice5Box::Scored {
+            self.rules.dice5_bonus
+        } else {
+            0
+        };
+
+        card.preview(hand)
+            .into_iter()
+            .map(|(id, points)| {
+                let line = self.lines.iter().position(|&l| l == id).unwrap_or(0);
+                let future = self.value(self.child(state, line, points));
+                (id, f64::from(points + bonus) + future)
+            })
+            .collect()
+    }
+
+    /// The value of each full hand with no rolls left: the best line for it.
+    fn hand_values(&mut self, state: State) -> Vec<f64> {
+        let card = self.card_for(state);
+        let mut values = vec![0.0; self.combos.len()];
+
+        for h in self.combos.hands() {
+            let hand = Dice::roll_fake(self.combos.faces(h));
+            values[h] = self
+                .choices(state, &card, &hand)
+                .iter()
+                .map(|&(_, value)| value)
+                .fold(0.0, f64::max);
+        }
+        values
+    }
+
+    /// The expected points still to come from a position between turns.
+    fn value(&mut self, state: State) -> f64 {
+        let all = (1u32 << self.lines.len()) - 1;
+        if state.filled == all {
+            let bonus = self.upper_cap > 0 && state.upper >= self.upper_cap;
+            return if bonus {
+                f64::from(self.rules.upper_bonus)
+            } else {
+                0.0
+            };
+        }
+        if let Some(&value) = self.table.get(&state.key()) {
+            return f64::from(value);
+        }
+
+        let values = self.hand_values(state);
+        let keeps = self
+            .combos
+            .keep_values_with(values, Dice::ROLLS_PER_TURN as usize);
+        let empty = self.combos.of_size(0).start;
+        // Kept as f32 to halve the table; rounded now so a loaded table
+        // gives the same answers as a fresh one.
+        let value = keeps[empty] as f32;
+
+        self.table.insert(state.key(), value);
+        f64::from(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::LineId as L;
+    use super::*;
+
+    /// A card with every line filled except `open`.
+    fn card_with_open(rules: &RuleSet, open: &[LineId]) -> ScoreCardData {
+        let mut card = get_new_scorecard_data(rules.clone());
+        for line in card.line.iter_mut() {
+            if !open.contains(&line.id) {
+                line.value = Some(0);
+            }
+        }
+        card
+    }
+
+    #[test]
+    fn chance_alone_is_worth_seventy_thirds() {
+        let rules = RuleSet::default();
+        let mut solver = Solver::new(&rules).unwrap();
+        let card = card_with_open(&rules, &[L::Chance]);
+
+        let expected = solver.expected_score(&card).unwrap();
+        assert!((expected - 70.0 / 3.0).abs() < 1e-4, "{}", expected);
+    }
+
+    #[test]
+    fn dice5_alone_matches_the_known_odds() {
+        let rules = RuleSet::default();
+        let mut solver = Solver::new(&rules).unwrap();
+        let card = card_with_open(&rules, &[L::Dice5]);
+
+        // 2,783,176 of the 6^10 ways to play a turn end in a Dice5.
+        let odds = 2_783_176.0 / 60_466_176.0;
+        let expected = solver.expected_score(&card).unwrap();
+        assert!((expected - 50.0 * odds).abs() < 1e-4, "{}", expected);
+    }
+
+    #[test]
+    fn keeps_the_sixes() {
+        let rules = RuleSet::default();
+        let mut solver = Solver::new(&rules).unwrap();
+        let card = card_with_open(&rules, &[L::Six]);
+        let hand = Dice::roll_fake(vec![6, 1, 6, 2, 3]);
+
+        let keep = solver.best_keep(&card, &hand).unwrap().unwrap();
+        assert_eq!(keep.mask, vec![false, true, false, true, true]);
+    }
+
+    #[test]
+    fn no_keep_without_rolls() {
+        let rules = RuleSet::default();
+        let mut solver = Solver::new(&rules).unwrap();
+        let card = card_with_open(&rules, &[L::Six]);
+        let mut hand = Dice::roll_fake(vec![6, 1, 6, 2, 3]);
+        hand.rolls_left = 0;
+
+        assert_eq!(solver.best_keep(&card, &hand), Ok(None));
+    }
+
+    #[test]
+    fn refuses_hands_that_are_not_dice() {
+        let rules = RuleSet::default();
+        let mut solver = Solver::new(&rules).unwrap();
+        let card = card_with_open(&rules, &[L::Six]);
+
+        let short = Dice::roll_fake(vec![6, 6]);
+        assert_eq!(solver.best_keep(&card, &short), Err(GameError::DiceCount));
+        assert_eq!(solver.best_line(&card, &short), Err(GameError::DiceCount));
+
+        let seven = Dice::roll_fake(vec![6, 1, 7, 2, 3]);
+        assert_eq!(
+            solver.best_keep(&card, &seven),
+            Err(GameError::InvalidDieFace)
+        );
+        assert_eq!(
+            solver.best_line(&card, &seven),
+            Err(GameError::InvalidDieFace)
+        );
+    }
+
+    #[test]
+    fn scores_the_big_chance_and_leaves_the_aces() {
+        let rules = RuleSet::default();
+        let mut solver = Solver::new(&rules).unwrap();
+        let card = card_with_open(&rules, &[L::Ace, L::Chance]);
+        let hand = Dice::roll_fake(vec![6, 6, 6, 6, 5]);
+
+        let (line, expected) = solver.best_line(&card, &hand).unwrap().unwrap();
+        assert_eq!(line, L::Chance);
+        assert!(expected > 29.0);
+    }
+
+    #[test]
+    fn counts_the_upper_bonus() {
+        let rules = RuleSet::default();
+        let mut solver = Solver::new(&rules).unwrap();
+        let mut card = card_with_open(&rules, &[L::Ace, L::Chance]);
+        for line in card.line.iter_mut() {
+            line.value = match line.id {
+                L::Six => Some(30),
+                L::Five => Some(25),
+                L::Four => Some(4),
+                L::Three => Some(3),
+                _ => line.value,
+            };
+        }
+
+        // One ace makes 63, which is worth far more than a few points of
+        // chance.
+        let hand = Dice::roll_fake(vec![1, 2, 6, 6, 6]);
+        let (line, _) = solver.best_line(&card, &hand).unwrap().unwrap();
+        assert_eq!(line, L::Ace);
+    }
+
+    #[test]
+    fn refuses_rules_with_dependent_turns() {
+        assert!(Solver::new(&RuleSet::maxi()).is_err());
+        assert!(Solver::new(&RuleSet::generala()).is_err());
+        assert!(Solver::new(&RuleSet::yacht()).is_ok());
+    }
+
+    #[test]
+    fn table_survives_a_round_trip_to_disk() {
+        let rules = RuleSet::default();
+        let path = std::env::temp_dir().join(format!("dice5-solver-{}.bin", std::process::id()));
+        let card = card_with_open(&rules, &[L::Chance, L::FullHouse]);
+
+        let mut solver = Solver::new(&rules).unwrap();
+        let expected = solver.expected_score(&card).unwrap();
+        solver.save(&path).unwrap();
+
+        let mut loaded = Solver::load(&path, &rules).unwrap();
+        assert_eq!(loaded.table_len(), solver.table_len());
+        assert_eq!(loaded.expected_score(&card).unwrap(), expected);
+        assert_eq!(
+            Solver::load(&path, &RuleSet::house()).unwrap().table_len(),
+            0
+        );
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,417 @@
+//! Computer players.
+//!
+//! A `Strategy` looks at a game with the dice showing and says what to do
+//! next; `play_turn` carries the decisions out through the normal rules, so
+//! a bot can not do anything a player at the terminal could not.
+
+use super::advisor::{self, Goal};
+use super::combos::Combos;
+use super::solver::Solver;
+use super::triple::multiplier;
+use super::{calchand, Dice, DiceRoller, Game, GameError, LineId, Phase, RuleSet};
+use std::collections::HashMap;
+
+/// What to do with the dice on the table.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Decision {
+    /// Re-roll the dice flagged `true`.
+    Roll(Vec<bool>),
+    Score {
+        column: usize,
+        line: LineId,
+    },
+}
+
+pub trait Strategy: Send {
+    fn name(&self) -> &str;
+
+    /// Only asked while the dice are showing, so `game.dice()` is set.
+    fn decide(&mut self, game: &Game) -> Decision;
+
+    /// What went wrong inside the strategy, if it carried on regardless.
+    fn error(&self) -> Option<&str> {
+        None
+    }
+}
+
+/// Plays one whole turn, rolling first if the turn has not started.
+pub fn play_turn(game: &mut Game, strategy: &mut dyn Strategy) -> Result<(), GameError> {
+    if game.phase() == Phase::AwaitingFirstRoll {
+        game.roll(&[])?;
+    }
+
+    loop {
+        match strategy.decide(game) {
+            Decision::Roll(mask) => {
+                game.roll(&mask)?;
+            }
+            Decision::Score { column, line } => {
+                game.score_in(column, line)?;
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Plays a whole game and returns the final score.
+pub fn play_game(game: &mut Game, strategy: &mut dyn Strategy) -> Result<i16, GameError> {
+    while game.phase() != Phase::GameOver {
+        play_turn(game, strategy)?;
+    }
+    Ok(game.grand_total())
+}
+
+/// The line to score that `value` rates highest, given each line's points
+/// already multiplied for its column.
+fn best_score<F>(game: &Game, mut value: F) -> Decision
+where
+    F: FnMut(usize, LineId, i16) -> f64,
+{
+    let mut best: Option<(f64, Decision)> = None;
+    for (column, line, points) in game.preview() {
+        let rated = value(column, line, points * multiplier(column));
+        if best.as_ref().is_none_or(|&(b, _)| rated > b) {
+            best = Some((rated, Decision::Score { column, line }));
+        }
+    }
+    best.map_or(Decision::Roll(vec![]), |(_, decision)| decision)
+}
+
+/// Re-rolls as `advice` says, or scores when it says to keep everything.
+fn roll_or<F>(advice: Option<&advisor::Advice>, score: F) -> Decision
+where
+    F: FnOnce() -> Decision,
+{
+    match advice {
+        Some(advice) if advice.mask.iter().any(|&reroll| reroll) => {
+            Decision::Roll(advice.mask.clone())
+        }
+        _ => score(),
+    }
+}
+
+/// Re-rolls dice and picks lines at random.  Good for testing front ends,
+/// not for practice.
+pub struct Random {
+    roller: Box<dyn DiceRoller>,
+}
+
+impl Random {
+    pub fn new(roller: Box<dyn DiceRoller>) -> Self {
+        Self { roller }
+    }
+
+    /// A fair pick from `0..n`, built from die rolls so a seeded roller
+    /// gives the same picks everywhere.
+    fn pick(&mut self, n: usize) -> usize {
+        let faces = Dice::NUMBER_OF_FACES as usize;
+        loop {
+            let (mut value, mut range) = (0, 1);
+            while range < n {
+                value = value * faces + (self.roller.roll_die() as usize - 1);
+                range *= faces;
+            }
+            if value < range - range % n {
+                return value % n;
+            }
+        }
+    }
+}
+
+impl Strategy for Random {
+    fn name(&self) -> &str {
+        "random"
+    }
+
+    fn decide(&mut self, game: &Game) -> Decision {
+        let number_of_dice = game.dice().map_or(0, |d| d.dice.len());
+        let can_roll = game.phase() == Phase::Rolling;
+
+        if can_roll && self.pick(2) == 0 {
+            let mask = (0..number_of_dice).map(|_| self.pick(2) == 0).collect();
+            return Decision::Roll(mask);
+        }
+
+        let choices = game.preview();
+        if choices.is_empty() {
+            return Decision::Roll(vec![]);
+        }
+        let (column, line, _) = choices[self.pick(choices.len())];
+        Decision::Score { column, line }
+    }
+}
+
+/// Plays for the most points this turn and takes them.
+#[derive(Clone, Default, Debug)]
+pub struct Greedy;
+
+impl Strategy for Greedy {
+    fn name(&self) -> &str {
+        "greedy"
+    }
+
+    fn decide(&mut self, game: &Game) -> Decision {
+        let advice = match game.dice() {
+            Some(hand) => advisor::advise(game.card(), hand, Goal::Points).unwrap_or_default(),
+            None => vec![],
+        };
+        roll_or(advice.first(), || {
+            best_score(game, |_, _, points| f64::from(points))
+        })
+    }
+}
+
+/// Rates each line against its par, the points it averages when a whole
+/// turn is spent on it, and chases the upper bonus while it can still be
+/// made.
+#[derive(Clone, Debug)]
+pub struct Heuristic {
+    par: HashMap<LineId, f64>,
+}
+
+impl Heuristic {
+    pub fn new(rules: &RuleSet) -> Self {
+        let combos = Combos::new(rules.number_of_dice);
+        let empty = combos.of_size(0).start;
+        let hands: Vec<Dice> = (0..combos.len())
+            .map(|h| Dice::roll_fake(combos.faces(h)))
+            .collect();
+
+        let par = rules
+            .lines()
+            .into_iter()
+            .map(|line| {
+                let calc = calchand::get_calc(line, rules.scoring);
+                let mut values = vec![0.0; combos.len()];
+                for h in combos.hands() {
+                    values[h] = f64::from(calc(&hands[h], rules, false));
+                }
+                let rolls = Dice::ROLLS_PER_TURN as usize;
+                (line, combos.keep_values_with(values, rolls)[empty])
+            })
+            .collect();
+
+        Self { par }
+    }
+
+    fn rate(&self, game: &Game, column: usize, line: LineId, points: i16) -> f64 {
+        let scorecard = &game.card()[column];
+        let rules = &scorecard.rules;
+        let mut value = f64::from(points)
+            - self.par.get(&line).cloned().unwrap_or(0.0) * f64::from(multiplier(column));
+
+        let upper = scorecard.subtotal(LineId::UpperSubtotal).unwrap_or(0);
+        let face = (1..=Dice::NUMBER_OF_FACES).find(|&f| LineId::upper_for_face(f) == Some(line));
+        if let Some(face) = face {
+            if rules.upper_bonus > 0 && upper < rules.upper_bonus_threshold {
+                // The pace that just makes the threshold: three of each
+                // face for the usual 63.
+                let pace = f64::from(rules.upper_bonus_threshold) * f64::from(face) / 21.0;
+                let share = f64::from(rules.upper_bonus) / f64::from(rules.upper_bonus_threshold);
+                value += (f64::from(points) / f64::from(multiplier(column)) - pace) * share;
+            }
+        }
+        value
+    }
+
+    fn hand_value(&self, game: &Game, hand: &Dice) -> f64 {
+        game.card()
+            .iter()
+            .enumerate()
+            .flat_map(|(column, scorecard)| {
+                scorecard
+                    .preview(hand)
+                    .into_iter()
+                    .map(move |(line, points)| (column, line, points))
+            })
+            .map(|(column, line, points)| {
+                self.rate(game, column, line, points * multiplier(column))
+            })
+            .fold(f64::MIN, f64::max)
+    }
+}
+
+impl Strategy for Heuristic {
+    fn name(&self) -> &str {
+        "heuristic"
+    }
+
+    fn decide(&mut self, game: &Game) -> Decision {
+        let advice = match game.dice() {
+            Some(hand) => advisor::advise_by(hand, |final_hand| self.hand_value(game, final_hand))
+                .unwrap_or_default(),
+            None => vec![],
+        };
+        roll_or(advice.first(), || {
+            best_score(game, |column, line, points| {
+                self.rate(game, column, line, points)
+            })
+        })
+    }
+}
+
+/// Perfect solitaire play from the solver's table.  Falls back to greedy
+/// play for anything the solver does not cover, such as a Triple card.
+pub struct Optimal {
+    solver: Solver,
+}
+
+impl Optimal {
+    pub fn new(solver: Solver) -> Self {
+        Self { solver }
+    }
+
+    pub fn solver(&self) -> &Solver {
+        &self.solver
+    }
+}
+
+impl Strategy for Optimal {
+    fn name(&self) -> &str {
+        "optimal"
+    }
+
+    fn decide(&mut self, game: &Game) -> Decision {
+        let hand = match game.dice() {
+            Some(hand) if !game.is_triple() => hand,
+            _ => return Greedy.decide(game),
+        };
+
+        match self.solver.best_keep(game.scorecard(), hand) {
+            Ok(Some(ref keep)) if keep.mask.iter().any(|&reroll| reroll) => {
+                return Decision::Roll(keep.mask.clone());
+            }
+            Err(_) => return Greedy.decide(game),
+            _ => {}
+        }
+
+        match self.solver.best_line(game.scorecard(), hand) {
+            Ok(Some((line, _))) => Decision::Score { column: 0, line },
+            _ => Greedy.decide(game),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::LineId as L;
+    use super::super::SeededRoller;
+    use super::*;
+
+    fn seeded_game(seed: u64) -> Game {
+        let mut game = Game::new(RuleSet::default());
+        game.set_roller(Box::new(SeededRoller::new(seed)));
+        game
+    }
+
+    fn showing(dice: Vec<i8>) -> Game {
+        let mut game = seeded_game(1);
+        game.roll(&[]).unwrap();
+        game.cheat(dice).unwrap();
+        game
+    }
+
+    #[test]
+    fn every_strategy_finishes_a_game() {
+        let rules = RuleSet::default();
+        let strategies: Vec<Box<dyn Strategy>> = vec![
+            Box::new(Random::new(Box::new(SeededRoller::new(7)))),
+            Box::new(Greedy),
+            Box::new(Heuristic::new(&rules)),
+        ];
+
+        for mut strategy in strategies {
+            let mut game = seeded_game(1);
+            let total = play_game(&mut game, strategy.as_mut()).unwrap();
+            assert_eq!(game.phase(), Phase::GameOver, "{}", strategy.name());
+            assert_eq!(total, game.grand_total());
+        }
+    }
+
+    #[test]
+    fn seeded_random_play_repeats() {
+        let play = || {
+            let mut game = seeded_game(3);
+            let mut random = Random::new(Box::new(SeededRoller::new(9)));
+            play_game(&mut game, &mut random).unwrap();
+            game
+        };
+        assert_eq!(play(), play());
+    }
+
+    #[test]
+    fn greedy_takes_the_dice5() {
+        let game = showing(vec![5, 5, 5, 5, 5]);
+        assert_eq!(
+            Greedy.decide(&game),
+            Decision::Score {
+                column: 0,
+                line: L::Dice5
+            }
+        );
+    }
+
+    #[test]
+    fn greedy_rerolls_for_more() {
+        let game = showing(vec![1, 2, 1, 6, 3]);
+        assert!(matches!(Greedy.decide(&game), Decision::Roll(_)));
+    }
+
+    #[test]
+    fn heuristic_saves_the_chance_for_later() {
+        let mut game = showing(vec![1, 2, 3, 5, 6]);
+        game.roll(&[false; 5]).unwrap();
+        game.roll(&[false; 5]).unwrap();
+
+        let mut heuristic = Heuristic::new(&RuleSet::default());
+        assert_eq!(
+            Greedy.decide(&game),
+            Decision::Score {
+                column: 0,
+                line: L::Chance
+            }
+        );
+        assert_ne!(
+            heuristic.decide(&game),
+            Decision::Score {
+                column: 0,
+                line: L::Chance
+            }
+        );
+    }
+
+    #[test]
+    fn optimal_finishes_the_game() {
+        let rules = RuleSet::default();
+        let mut game = seeded_game(5);
+        for _ in 0..rules.lines().len() - 2 {
+            play_turn(&mut game, &mut Greedy).unwrap();
+        }
+
+        let mut optimal = Optimal::new(Solver::new(&rules).unwrap());
+        play_game(&mut game, &mut optimal).unwrap();
+        assert_eq!(game.phase(), Phase::GameOver);
+    }
+
+    #[test]
+    fn illegal_decisions_are_refused() {
+        struct Stubborn;
+        impl Strategy for Stubborn {
+            fn name(&self) -> &str {
+                "stubborn"
+            }
+            fn decide(&mut self, _: &Game) -> Decision {
+                Decision::Score {
+                    column: 2,
+                    line: L::Chance,
+                }
+            }
+        }
+
+        let mut game = seeded_game(1);
+        assert_eq!(
+            play_turn(&mut game, &mut Stubborn),
+            Err(GameError::UnknownColumn)
+        );
+    }
+}
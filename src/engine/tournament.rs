@@ -0,0 +1,264 @@
+//! Many seeded games between strategies, with the numbers to tell them
+//! apart.
+//!
+//! Game `i` of a tournament is rolled by a `KeyedRoller` keyed `seed + i`
+//! for every strategy.  Each die comes from where it is rolled, so every
+//! strategy meets the same first roll each turn and the same die wherever
+//! it re-rolls the same place, whatever it kept before.  The comparison is
+//! paired: the difference between two strategies is measured game by game,
+//! which takes out most of the luck of the dice.  A game is won by an
+//! outright win first and the total after that, as in `hotseat::standings`.
+
+use super::strategy::{self, Strategy};
+use super::{Game, GameError, KeyedRoller, LineId, RuleSet};
+
+/// The 95% two-sided normal quantile.
+const Z95: f64 = 1.96;
+
+/// How one strategy did over the tournament.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Standing {
+    pub name: String,
+    pub mean: f64,
+    pub std_dev: f64,
+    /// A 95% confidence interval for the mean.
+    pub interval: (f64, f64),
+    /// The share of games won, outright wins first and then the total, with
+    /// ties split between the winners.  Only strategies without an error
+    /// take part, so this is zero for one with an error.
+    pub win_rate: f64,
+    /// The mean score less the baseline's, game by game, with a 95%
+    /// confidence interval.  Zero for the baseline itself and for a
+    /// strategy with an error.
+    pub difference: (f64, (f64, f64)),
+    /// The average points scored on each line, in card order.
+    pub lines: Vec<(LineId, f64)>,
+    /// What went wrong with the strategy, if anything.  Someone else played
+    /// its games from then on, so its numbers do not count.
+    pub error: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Report {
+    pub games: usize,
+    pub standings: Vec<Standing>,
+    /// The first strategy without an error, which the others are compared
+    /// with.  `None` when every strategy went wrong.
+    pub baseline: Option<usize>,
+}
+
+/// Plays `games` games with every strategy, on the dice from `seed`.
+pub fn run(
+    rules: &RuleSet,
+    strategies: &mut [Box<dyn Strategy>],
+    games: usize,
+    seed: u64,
+) -> Result<Report, GameError> {
+    let lines = rules.lines();
+    let mut scores = vec![vec![]; strategies.len()];
+    let mut line_totals = vec![vec![0i64; lines.len()]; strategies.len()];
+    let mut results = vec![];
+
+    for i in 0..games {
+        let mut game_results = vec![];
+        for (s, strategy) in strategies.iter_mut().enumerate() {
+            let game = play(rules, strategy.as_mut(), seed.wrapping_add(i as u64))?;
+            let total = game.grand_total();
+
+            for (l, line) in game.scorecard().line.iter().enumerate() {
+                line_totals[s][l] += i64::from(line.value.unwrap_or(0));
+            }
+            scores[s].push(f64::from(total));
+            game_results.push((game.card().iter().any(|c| c.won), total));
+        }
+        results.push(game_results);
+    }
+
+    // A strategy that went wrong had its games played for it, so it is
+    // left out of the wins and the comparison.
+    let valid: Vec<bool> = strategies.iter().map(|s| s.error().is_none()).collect();
+    let mut wins = vec![0.0; strategies.len()];
+    for game_results in &results {
+        let contenders = || (0..strategies.len()).filter(|&s| valid[s]);
+        let best = contenders().map(|s| game_results[s]).max();
+        let winners: Vec<usize> = contenders()
+            .filter(|&s| Some(game_results[s]) == best)
+            .collect();
+        for &s in &winners {
+            wins[s] += 1.0 / winners.len() as f64;
+        }
+    }
+    let baseline = valid.iter().position(|&v| v);
+
+    let standings = strategies
+        .iter()
+        .enumerate()
+        .map(|(s, strategy)| {
+            let (mean, std_dev, interval) = summary(&scores[s]);
+            let (difference, difference_interval) = match baseline {
+                Some(b) if valid[s] => {
+                    let differences: Vec<f64> = scores[s]
+                        .iter()
+                        .zip(&scores[b])
+                        .map(|(a, b)| a - b)
+                        .collect();
+                    let (difference, _, interval) = summary(&differences);
+                    (difference, interval)
+                }
+                _ => (0.0, (0.0, 0.0)),
+            };
+
+            Standing {
+                name: strategy.name().to_string(),
+                mean,
+                std_dev,
+                interval,
+                win_rate: wins[s] / games.max(1) as f64,
+                difference: (difference, difference_interval),
+                lines: lines
+                    .iter()
+                    .zip(&line_totals[s])
+                    .map(|(&id, &total)| (id, total as f64 / games.max(1) as f64))
+                    .collect(),
+                error: strategy.error().map(str::to_string),
+            }
+        })
+        .collect();
+
+    Ok(Report {
+        games,
+        standings,
+        baseline,
+    })
+}
+
+/// One game of the tournament, rolled by the roller keyed `key`.
+fn play(rules: &RuleSet, strategy: &mut dyn Strategy, key: u64) -> Result<Game, GameError> {
+    let mut game = Game::new(rules.clone());
+    game.set_roller(Box::new(KeyedRoller::new(key)));
+    strategy::play_game(&mut game, strategy)?;
+    Ok(game)
+}
+
+/// The mean, the sample standard deviation and a 95% confidence interval
+/// for the mean.
+fn summary(values: &[f64]) -> (f64, f64, (f64, f64)) {
+    let n = values.len() as f64;
+    if values.is_empty() {
+        return (0.0, 0.0, (0.0, 0.0));
+    }
+
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = if values.len() > 1 {
+        values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)
+    } else {
+        0.0
+    };
+    let std_dev = variance.sqrt();
+    let margin = Z95 * std_dev / n.sqrt();
+    (mean, std_dev, (mean - margin, mean + margin))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::strategy::{Decision, Greedy, Random};
+    use super::super::{DieFace, Event, SeededRoller};
+    use super::*;
+
+    fn first_rolls(game: &Game) -> Vec<&[DieFace]> {
+        game.log()
+            .iter()
+            .filter_map(|event| match event {
+                Event::Roll { mask, dice } if mask.is_empty() => Some(dice.as_slice()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn every_strategy_gets_the_same_first_rolls() {
+        let rules = RuleSet::default();
+        for key in 0..5 {
+            let greedy = play(&rules, &mut Greedy, key).unwrap();
+            let random = play(
+                &rules,
+                &mut Random::new(Box::new(SeededRoller::new(key))),
+                key,
+            )
+            .unwrap();
+
+            assert_eq!(first_rolls(&greedy).len(), rules.lines().len());
+            assert_eq!(first_rolls(&greedy), first_rolls(&random));
+            assert_ne!(greedy.log(), random.log());
+        }
+    }
+
+    #[test]
+    fn summary_of_known_values() {
+        let (mean, std_dev, (low, high)) = summary(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]);
+        assert_eq!(mean, 5.0);
+        assert!((std_dev - 2.138_089_935).abs() < 1e-6);
+        assert!(low < mean && mean < high);
+        assert!((high - low - 2.0 * Z95 * std_dev / 8f64.sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn same_strategy_same_dice_same_scores() {
+        let mut strategies: Vec<Box<dyn Strategy>> = vec![Box::new(Greedy), Box::new(Greedy)];
+        let report = run(&RuleSet::default(), &mut strategies, 5, 11).unwrap();
+
+        let (first, second) = (&report.standings[0], &report.standings[1]);
+        assert_eq!(first.mean, second.mean);
+        assert_eq!(second.difference, (0.0, (0.0, 0.0)));
+        assert_eq!(first.win_rate, 0.5);
+    }
+
+    #[test]
+    fn greedy_beats_random() {
+        let mut strategies: Vec<Box<dyn Strategy>> = vec![
+            Box::new(Random::new(Box::new(SeededRoller::new(3)))),
+            Box::new(Greedy),
+        ];
+        let report = run(&RuleSet::default(), &mut strategies, 10, 1).unwrap();
+
+        let (random, greedy) = (&report.standings[0], &report.standings[1]);
+        assert_eq!(report.games, 10);
+        assert!(greedy.win_rate > random.win_rate);
+        assert!(greedy.difference.1 .0 > 0.0);
+        assert_eq!(greedy.lines.len(), RuleSet::default().lines().len());
+        assert_eq!(greedy.error, None);
+
+        let line_sum: f64 = greedy.lines.iter().map(|&(_, points)| points).sum();
+        assert!(line_sum <= greedy.mean + 1e-9);
+    }
+
+    /// Greedy's play under a name that owns up to going wrong.
+    struct Broken;
+
+    impl Strategy for Broken {
+        fn name(&self) -> &str {
+            "broken"
+        }
+
+        fn decide(&mut self, game: &Game) -> Decision {
+            Greedy.decide(game)
+        }
+
+        fn error(&self) -> Option<&str> {
+            Some("broken")
+        }
+    }
+
+    #[test]
+    fn a_strategy_that_went_wrong_is_left_out() {
+        let mut strategies: Vec<Box<dyn Strategy>> = vec![Box::new(Broken), Box::new(Greedy)];
+        let report = run(&RuleSet::default(), &mut strategies, 5, 11).unwrap();
+
+        let (broken, greedy) = (&report.standings[0], &report.standings[1]);
+        assert_eq!(report.baseline, Some(1));
+        assert_eq!(broken.error.as_deref(), Some("broken"));
+        assert_eq!((broken.win_rate, greedy.win_rate), (0.0, 1.0));
+        assert_eq!(broken.difference, (0.0, (0.0, 0.0)));
+        assert_eq!(greedy.difference, (0.0, (0.0, 0.0)));
+    }
+}
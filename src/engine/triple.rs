@@ -0,0 +1,121 @@
+use super::{get_new_scorecard_data, Dice, DiceRoller, GameError, LineId, RuleSet, ScoreCardData};
+
+/// Column multipliers for a Triple Dice5 card, left to right.
+pub const MULTIPLIERS: [i16; 3] = [1, 2, 3];
+
+pub fn get_new_triple_card(rules: &RuleSet) -> Vec<ScoreCardData> {
+    MULTIPLIERS
+        .iter()
+        .map(|_| get_new_scorecard_data(rules.clone()))
+        .collect()
+}
+
+pub fn multiplier(column: usize) -> i16 {
+    MULTIPLIERS.get(column).cloned().unwrap_or(1)
+}
+
+pub fn play(
+    card: &mut [ScoreCardData],
+    column: usize,
+    zid: LineId,
+    hand: &Dice,
+) -> Result<i16, GameError> {
+    match card.get_mut(column) {
+        None => Err(GameError::UnknownColumn),
+        Some(scorecard) => scorecard.play(zid, hand),
+    }
+}
+
+/// Deals a new hand.  Rolls saved in any column all belong to the player.
+pub fn next_hand(card: &mut [ScoreCardData], roller: &mut dyn DiceRoller) -> Dice {
+    let saved: i8 = card.iter_mut().map(|c| c.take_saved_rolls()).sum();
+    let number_of_dice = card.first().map_or(5, |c| c.rules.number_of_dice);
+
+    let mut hand = Dice::first_roll(number_of_dice, roller);
+    hand.rolls_left += saved;
+    hand
+}
+
+pub fn column_total(card: &[ScoreCardData], column: usize) -> i16 {
+    card.get(column).map_or(0, |scorecard| {
+        super::calc_grand_total(scorecard) * multiplier(column)
+    })
+}
+
+pub fn grand_total(card: &[ScoreCardData]) -> i16 {
+    (0..card.len())
+        .map(|column| column_total(card, column))
+        .sum()
+}
+
+pub fn game_over(card: &[ScoreCardData]) -> bool {
+    card.iter().all(|scorecard| scorecard.game_over())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::LineId as L;
+    use super::super::OsRoller;
+    use super::*;
+
+    #[test]
+    fn new_triple_card_has_three_columns() {
+        let card = get_new_triple_card(&RuleSet::default());
+        assert_eq!(card.len(), 3);
+        assert!(!game_over(&card));
+    }
+
+    #[test]
+    fn play_only_touches_chosen_column() {
+        let mut card = get_new_triple_card(&RuleSet::default());
+        let hand = Dice::roll_fake(vec![2, 2, 3, 3, 3]);
+
+        assert_eq!(play(&mut card, 1, L::FullHouse, &hand), Ok(25));
+        assert_eq!(card[0].get_line_by_id(L::FullHouse).unwrap().value, None);
+        assert_eq!(
+            card[1].get_line_by_id(L::FullHouse).unwrap().value,
+            Some(25)
+        );
+        assert_eq!(
+            play(&mut card, 1, L::FullHouse, &hand),
+            Err(GameError::AlreadySet)
+        );
+        assert_eq!(play(&mut card, 0, L::FullHouse, &hand), Ok(25));
+    }
+
+    #[test]
+    fn play_unknown_column() {
+        let mut card = get_new_triple_card(&RuleSet::default());
+        let hand = Dice::roll_fake(vec![2, 2, 3, 3, 3]);
+
+        assert_eq!(
+            play(&mut card, 3, L::FullHouse, &hand),
+            Err(GameError::UnknownColumn)
+        );
+    }
+
+    #[test]
+    fn grand_total_applies_multipliers() {
+        let mut card = get_new_triple_card(&RuleSet::default());
+        let hand = Dice::roll_fake(vec![2, 2, 3, 3, 3]);
+
+        for column in 0..3 {
+            play(&mut card, column, L::Chance, &hand).unwrap();
+        }
+
+        assert_eq!(column_total(&card, 2), 13 * 3);
+        assert_eq!(grand_total(&card), 13 + 13 * 2 + 13 * 3);
+    }
+
+    #[test]
+    fn next_hand_collects_saved_rolls_from_every_column() {
+        let mut card = get_new_triple_card(&RuleSet::maxi());
+        card[0].saved_rolls = 1;
+        card[2].saved_rolls = 2;
+
+        let hand = next_hand(&mut card, &mut OsRoller);
+        assert_eq!(hand.dice.len(), 6);
+        assert_eq!(hand.rolls_left, Dice::ROLLS_PER_TURN - 1 + 3);
+        assert!(card.iter().all(|c| c.saved_rolls == 0));
+    }
+}
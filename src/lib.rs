@@ -0,0 +1,22 @@
+//! The Dice5 scoring engine, usable on its own by bots, servers and
+//! analytics tools.  The terminal game in `main.rs` is a thin front end on
+//! top of it.
+//!
+//! # Stability
+//!
+//! * [`engine::Game`] is the way to play.  It checks every rule, so front
+//!   ends only ever call `roll`, `score_in` and `legal_actions`.
+//! * [`engine::Dice`], [`engine::LineId`] and [`engine::ScoreCardData`] are
+//!   stable.  New `LineId` variants may be added as rule sets are added, so
+//!   match on them with a wildcard arm.
+//! * [`engine::RuleSet`] presets keep their names and values.  New fields
+//!   may be added; build custom rules with struct-update syntax from a
+//!   preset.
+//! * [`engine::GameError`] may gain variants.  It and `LineId` are
+//!   `#[non_exhaustive]`, so the compiler asks for the wildcard arm.
+//! * [`text`] holds the English names for lines.  The short codes from
+//!   `text::get_short_name` are stable, as game notation and the bot
+//!   protocol are written in them; the long names may change.
+
+pub mod engine;
+pub mod text;
@@ -1,6 +1,4 @@
-mod engine;
 mod term;
-mod text;
 
 fn main() {
     term::main::main();
@@ -0,0 +1,134 @@
+use super::replay::describe;
+use super::saves;
+use super::ui;
+use rust_dice5::engine::external::External;
+use rust_dice5::engine::hotseat;
+use rust_dice5::engine::solver::Solver;
+use rust_dice5::engine::strategy::{self, Greedy, Heuristic, Optimal, Random, Strategy};
+use rust_dice5::engine::{DiceRoller, Game, OsRoller, Phase, RuleSet, SeededRoller};
+
+pub const NAMES: [&str; 4] = ["random", "greedy", "heuristic", "optimal"];
+
+/// `exec:python3 bot.py` runs a bot that speaks the `engine::external`
+/// protocol.
+pub const EXTERNAL: &str = "exec:";
+
+/// A computer opponent with its own card and dice.
+pub struct Bot {
+    strategy: Box<dyn Strategy>,
+    game: Game,
+}
+
+impl Bot {
+    pub fn name(&self) -> &str {
+        self.strategy.name()
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+}
+
+/// Dice for the `n`th bot: seeded from the game's seed so a seeded game
+/// plays out the same way every time.
+fn roller(seed: Option<u64>, n: u64) -> Box<dyn DiceRoller> {
+    match seed {
+        Some(seed) => Box::new(SeededRoller::new(seed.wrapping_add(n))),
+        None => Box::new(OsRoller),
+    }
+}
+
+pub fn strategy_for(
+    name: &str,
+    rules: &RuleSet,
+    triple: bool,
+    roller: Box<dyn DiceRoller>,
+) -> Result<Box<dyn Strategy>, String> {
+    match name {
+        "random" => Ok(Box::new(Random::new(roller))),
+        "greedy" => Ok(Box::new(Greedy)),
+        "heuristic" => Ok(Box::new(Heuristic::new(rules))),
+        "optimal" if triple => Err("The optimal bot only plays a single card.".to_string()),
+        "optimal" => {
+            let path = saves::solver_path(rules);
+            let solver = Solver::load(&path, rules).map_err(|err| {
+                format!(
+                    "The optimal bot cannot play the {} rules: {}",
+                    rules.name, err
+                )
+            })?;
+            if solver.table_len() == 0 {
+                println!("The optimal bot has not studied these rules.  Its first turns");
+                println!("are slow; '--solve' prepares it ahead of time.");
+            }
+            Ok(Box::new(Optimal::new(solver)))
+        }
+        _ => match name.strip_prefix(EXTERNAL) {
+            Some(command) => match External::from_command_line(command) {
+                Ok(bot) => Ok(Box::new(bot)),
+                Err(err) => Err(format!("Cannot start the bot '{}': {}", command, err)),
+            },
+            None => Err(format!(
+                "There is no '{}' bot.  Try one of: {}, or {}<command>",
+                name,
+                NAMES.join(", "),
+                EXTERNAL
+            )),
+        },
+    }
+}
+
+/// A bot for every `--bot <name>` on the command line.
+pub fn from_args(rules: &RuleSet, triple: bool, seed: Option<u64>) -> Result<Vec<Bot>, String> {
+    let args: Vec<String> = std::env::args().collect();
+    let names = args
+        .iter()
+        .enumerate()
+        .filter(|&(_, a)| a == "--bot")
+        .map(|(i, _)| args.get(i + 1).cloned().unwrap_or_default());
+
+    let mut bots = vec![];
+    for (n, name) in names.enumerate() {
+        let n = n as u64 * 2;
+        let strategy = strategy_for(&name, rules, triple, roller(seed, n + 2))?;
+        let mut game = if triple {
+            Game::new_triple(rules.clone())
+        } else {
+            Game::new(rules.clone())
+        };
+        game.set_roller(roller(seed, n + 1));
+        bots.push(Bot { strategy, game });
+    }
+    Ok(bots)
+}
+
+/// Every bot plays its turn, and says what it did.
+pub fn take_turns(bots: &mut [Bot]) {
+    for bot in bots.iter_mut() {
+        if bot.game.phase() == Phase::GameOver {
+            continue;
+        }
+
+        let start = bot.game.log().len();
+        let was_fine = bot.strategy.error().is_none();
+        let played = strategy::play_turn(&mut bot.game, bot.strategy.as_mut());
+        let name = bot.strategy.name();
+        if let (true, Some(err)) = (was_fine, bot.strategy.error()) {
+            println!("{} went wrong: {}.  Greedy plays for it now.", name, err);
+        }
+        for event in &bot.game.log()[start..] {
+            println!("{}: {}", name, describe(event, &bot.game));
+        }
+        match played {
+            Ok(()) => println!("{}: {} points so far.", name, bot.game.grand_total()),
+            Err(err) => println!("{} is stuck: {}", name, err),
+        }
+    }
+}
+
+/// The final ranking of the player and the bots.
+pub fn show_standings(human: &Game, bots: &[Bot]) {
+    let mut games = vec![("You", human)];
+    games.extend(bots.iter().map(|bot| (bot.name(), bot.game())));
+    ui::show_standings(&hotseat::standings(&games));
+}
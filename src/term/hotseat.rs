@@ -0,0 +1,169 @@
+use super::bots::{self, Bot};
+use super::main::{advise, cheat, offer_resume, play, read_line, reroll, show_odds, Resume};
+use super::saves;
+use super::scores;
+use super::ui;
+use rust_dice5::engine::hotseat::{self, HotSeat};
+use rust_dice5::engine::{notation, Action, Phase, RuleSet, SeededRoller};
+
+/// `--players Ann,Bob,Cy` seats those players at one terminal.
+pub fn names_from_args() -> Option<Vec<String>> {
+    let args: Vec<String> = std::env::args().collect();
+
+    args.iter().position(|a| a == "--players").map(|i| {
+        args.get(i + 1)
+            .map(|names| {
+                names
+                    .split(',')
+                    .map(|name| name.trim().to_string())
+                    .filter(|name| !name.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    })
+}
+
+fn show_table(seat: &HotSeat) {
+    ui::show_hotseat_card(seat);
+    if let Some(hand) = seat.current().game.dice() {
+        ui::show_hand(hand);
+    }
+}
+
+/// Rolls for the player whose turn it is and shows the table.
+fn start_turn(seat: &mut HotSeat) {
+    let game = &mut seat.current_mut().game;
+    if game.phase() == Phase::AwaitingFirstRoll {
+        game.roll(&[]).expect("first roll");
+    }
+    show_table(seat);
+}
+
+fn autosave(seat: &HotSeat) {
+    if let Err(err) = saves::save(saves::AUTOSAVE_HOTSEAT, seat) {
+        println!("Could not autosave: {}", err);
+    }
+}
+
+pub fn run(names: Vec<String>, rules: RuleSet, seed: Option<u64>, mut bots: Vec<Bot>) {
+    let mut seat = match HotSeat::new(names, rules) {
+        Ok(seat) => seat,
+        Err(err) => {
+            println!("Cannot start: {}.", err);
+            return;
+        }
+    };
+    if let Some(seed) = seed {
+        for (i, player) in seat.players_mut().iter_mut().enumerate() {
+            let seed = seed.wrapping_sub(i as u64);
+            player.game.set_roller(Box::new(SeededRoller::new(seed)));
+        }
+    }
+
+    // Nothing is autosaved until the players have turned the old game down.
+    if saves::exists(saves::AUTOSAVE_HOTSEAT) {
+        match offer_resume(saves::AUTOSAVE_HOTSEAT) {
+            Resume::Saved(saved) => seat = saved,
+            Resume::Fresh => {}
+            Resume::NoAnswer => return,
+        }
+    }
+    start_turn(&mut seat);
+
+    while !seat.is_over() {
+        let name = seat.current().name.clone();
+        if seat.current().game.legal_actions().contains(&Action::Roll) {
+            println!("{}'s turn.  'play', 'roll' or 'cheat' >> ", name);
+        } else {
+            println!("{}, no rolls left.  'play' or 'cheat' >> ", name);
+        }
+
+        let line = match read_line() {
+            Some(line) => line,
+            None => {
+                autosave(&seat);
+                println!(
+                    "Out of input.  Type 'load {}' to resume.",
+                    saves::AUTOSAVE_HOTSEAT
+                );
+                return;
+            }
+        };
+        let words: Vec<_> = line.split_whitespace().collect();
+
+        match words[0] {
+            "play" => match words.as_slice() {
+                [_, slot] => {
+                    if play(slot, 0, &mut seat.current_mut().game) {
+                        if seat.next_turn() {
+                            bots::take_turns(&mut bots);
+                        }
+                        if !seat.is_over() {
+                            start_turn(&mut seat);
+                        }
+                        autosave(&seat);
+                    }
+                }
+                _ => println!("Play in a position, like 'play fh'"),
+            },
+            "roll" if reroll(&words, &mut seat.current_mut().game) => {
+                show_table(&seat);
+                autosave(&seat);
+            }
+            "cheat" => {
+                if cheat(&mut seat.current_mut().game) {
+                    show_table(&seat);
+                }
+                autosave(&seat);
+            }
+            "advise" => advise(&words, &seat.current().game),
+            "odds" => show_odds(&words, &seat.current().game),
+            "save" => match words.as_slice() {
+                [_, name] => match saves::save(name, &seat) {
+                    Ok(()) => println!("Saved as {}.", name),
+                    Err(err) => println!("Could not save {}: {}", name, err),
+                },
+                _ => println!("Save under a name, like 'save evening'"),
+            },
+            "load" => match words.as_slice() {
+                [_, name] => match saves::load_unfinished::<HotSeat>(name) {
+                    Ok(loaded) => {
+                        seat = loaded;
+                        println!("Loaded {}.", name);
+                        start_turn(&mut seat);
+                    }
+                    Err(err) => println!("Could not load {}: {}", name, err),
+                },
+                _ => println!("Load a saved game by name, like 'load evening'"),
+            },
+            "saves" => match saves::list() {
+                Ok(ref names) if names.is_empty() => println!("There are no saved games."),
+                Ok(names) => println!("Saved games: {}", names.join(", ")),
+                Err(err) => println!("Could not list the saved games: {}", err),
+            },
+            "notation" => {
+                let record = notation::Record {
+                    players: seat.players().iter().map(|p| p.name.clone()).collect(),
+                    seed,
+                    games: seat.players().iter().map(|p| p.game.clone()).collect(),
+                };
+                print!("{}", notation::write(&record));
+            }
+            _ => {}
+        };
+    }
+
+    if saves::exists(saves::AUTOSAVE_HOTSEAT) {
+        saves::remove(saves::AUTOSAVE_HOTSEAT).ok();
+    }
+
+    show_table(&seat);
+    let mut games: Vec<_> = seat
+        .players()
+        .iter()
+        .map(|p| (p.name.as_str(), &p.game))
+        .collect();
+    scores::record(&games);
+    games.extend(bots.iter().map(|bot| (bot.name(), bot.game())));
+    ui::show_standings(&hotseat::standings(&games));
+}
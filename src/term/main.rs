@@ -1,105 +1,589 @@
-use super::super::engine;
-use super::super::text;
+use super::bots;
+use super::hotseat;
+use super::net;
+use super::replay;
+use super::saves::{self, Save};
+use super::scores;
+use super::tournament;
 use super::ui;
-use engine::SetError as SErr;
-use engine::{get_new_scorecard_data, Dice, ScoreCardData};
+use engine::advisor::{self, Goal};
+use engine::notation;
+use engine::odds;
+use engine::solver::Solver;
+use engine::triple;
+use engine::GameError as GErr;
+use engine::{Action, Dice, Game, LineId, Phase, PlayOrder, RuleSet, SeededRoller};
+use rust_dice5::engine;
+use rust_dice5::text;
 use std::io::BufRead;
 
-fn read_line() -> String {
+/// The next non-blank line, or `None` once the input is closed.
+pub fn read_line() -> Option<String> {
     for line in std::io::stdin().lock().lines() {
-        let line = line.expect("Read error");
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => return None,
+        };
 
         let words: Vec<_> = line.split_whitespace().collect();
 
         if !words.is_empty() {
-            return line;
+            return Some(line);
         }
     }
-    panic!("Out of input");
+    None
 }
 
-fn play(slot: &str, hand: &Dice, scorecard: &mut ScoreCardData) -> bool {
-    let id = text::get_id_by_short_name(&slot);
-    if id.is_err() {
+fn autosave(game: &Game) {
+    if let Err(err) = saves::save(saves::AUTOSAVE, game) {
+        println!("Autosave failed: {}", err);
+    }
+}
+
+/// The line a short name like `fh` stands for, if this card has it.
+pub fn line_for(slot: &str, game: &Game) -> Option<LineId> {
+    let id = text::get_id_by_short_name(slot);
+    if id.is_none() {
         println!("I have no idea what this means: {}.", slot);
-        return false;
+        return None;
     }
 
     let id = id.unwrap();
 
-    let point_result = scorecard.play(id, &hand);
+    if !game.scorecard().has_line(id) {
+        println!(
+            "There is no {} line in these rules.",
+            text::get_long_name(id)
+        );
+        return None;
+    }
+    Some(id)
+}
+
+pub fn play(slot: &str, column: usize, game: &mut Game) -> bool {
+    let id = match line_for(slot, game) {
+        Some(id) => id,
+        None => return false,
+    };
+
+    let point_result = game.score_in(column, id);
     let mut ret = false;
 
     match point_result {
-        Err(SErr::AlreadySet) => println!("A value for {} has already been set.", slot),
+        Err(GErr::AlreadySet) => println!("A value for {} has already been set.", slot),
+        Err(GErr::IllegalJoker) => {
+            let hand = game.dice().expect("dice on the table");
+            let legal: Vec<_> = game.card()[column]
+                .legal_lines(hand)
+                .iter()
+                .map(|&id| text::get_short_name(id))
+                .collect();
+            println!(
+                "Joker rules: this hand must be played on {}.",
+                legal.join(", ")
+            );
+        }
+        Err(GErr::OutOfOrder) => match game.card()[column].next_in_order() {
+            Some(next) => println!(
+                "Lines are played in order.  Next up is {}.",
+                text::get_rules_long_name(next, game.rules())
+            ),
+            None => println!("Lines are played in order."),
+        },
+        Err(GErr::UnknownColumn) => println!("There is no column {}.", column + 1),
+        Err(GErr::WrongPhase) => println!("You can't score right now."),
+        Err(err) => println!("{}", err),
         Ok(points) => {
-            let long_name = text::get_long_name(id);
-            println!("Played {} points on {}", points, long_name);
+            let long_name = text::get_rules_long_name(id, game.rules());
+            if game.is_triple() {
+                let multiplier = triple::multiplier(column);
+                println!(
+                    "Played {} (x{}) points on {}",
+                    points, multiplier, long_name
+                );
+            } else {
+                println!("Played {} points on {}", points, long_name);
+            }
             ret = true;
         }
     }
     ret
 }
 
+fn rules_from_args() -> Option<RuleSet> {
+    let args: Vec<String> = std::env::args().collect();
+
+    match args.iter().position(|a| a == "--rules") {
+        None => Some(RuleSet::default()),
+        Some(i) => args.get(i + 1).and_then(|name| RuleSet::by_name(name)),
+    }
+}
+
+/// `--seed <n>` rolls the same dice every time, for bug reports.
+pub fn seed_from_args() -> Option<Result<u64, String>> {
+    let args: Vec<String> = std::env::args().collect();
+
+    args.iter().position(|a| a == "--seed").map(|i| {
+        let seed = args.get(i + 1).cloned().unwrap_or_default();
+        seed.parse::<u64>().map_err(|_| seed)
+    })
+}
+
+pub fn show_card(game: &Game) {
+    if game.is_triple() {
+        ui::show_triple_card(game.card(), game.dice());
+    } else {
+        ui::show_card(game.scorecard(), game.dice());
+    }
+}
+
+/// The answer to the offer to resume an autosaved game.
+pub enum Resume<T> {
+    Saved(T),
+    /// Start afresh; the autosave will be overwritten.
+    Fresh,
+    /// The input ran out before an answer, so nothing should be written.
+    NoAnswer,
+}
+
+/// Asks whether to carry on with the game autosaved as `name`.
+pub fn offer_resume<T: Save>(name: &str) -> Resume<T> {
+    loop {
+        println!("An unfinished game was saved.  Resume it? (y/n) >> ");
+        let answer = match read_line() {
+            Some(answer) => answer.trim().to_lowercase(),
+            None => return Resume::NoAnswer,
+        };
+
+        match answer.as_str() {
+            "y" | "yes" => {
+                return match saves::load_unfinished(name) {
+                    Ok(saved) => Resume::Saved(saved),
+                    Err(err) => {
+                        println!("Could not load {}: {}.  Starting a new game.", name, err);
+                        Resume::Fresh
+                    }
+                }
+            }
+            "n" | "no" => return Resume::Fresh,
+            _ => {}
+        }
+    }
+}
+
+/// Starts the next turn, since the terminal always rolls straight away.
+fn first_roll(game: &mut Game) {
+    if game.phase() == Phase::AwaitingFirstRoll {
+        game.roll(&[]).expect("first roll");
+    }
+    show_table(game);
+}
+
+/// The card, with what the dice would score, and the dice themselves.
+fn show_table(game: &Game) {
+    show_card(game);
+    if let Some(hand) = game.dice() {
+        ui::show_hand(hand);
+    }
+}
+
+/// The dice to re-roll for `roll 1 2 3`, one flag per die.
+pub fn reroll_mask(words: &[&str], game: &Game) -> Option<Vec<bool>> {
+    if words.len() == 1 {
+        println!("Which die positions to roll?");
+        println!("Example 'roll 1 2 3' to re-roll the first three dice.");
+        return None;
+    }
+
+    let number_of_dice = game.dice().map_or(0, |d| d.dice.len());
+    let mut reroll_flags = vec![false; number_of_dice];
+
+    words[1..]
+        .iter()
+        .flat_map(|l| l.parse::<usize>())
+        .filter_map(|p| p.checked_sub(1))
+        .for_each(|p| {
+            if let Some(flag) = reroll_flags.get_mut(p) {
+                *flag = true
+            }
+        });
+    Some(reroll_flags)
+}
+
+/// `roll 1 2 3` re-rolls the dice in those positions.  Returns `true` if
+/// the dice were rolled.
+pub fn reroll(words: &[&str], game: &mut Game) -> bool {
+    let reroll_flags = match reroll_mask(words, game) {
+        Some(flags) => flags,
+        None => return false,
+    };
+
+    match game.roll(&reroll_flags) {
+        Ok(_) => true,
+        Err(err) => {
+            println!("{}", err);
+            false
+        }
+    }
+}
+
+/// Turns the dice on the table into all sixes.
+pub fn cheat(game: &mut Game) -> bool {
+    let dice = vec![6; game.rules().number_of_dice];
+    match game.cheat(dice) {
+        Ok(()) => true,
+        Err(err) => {
+            println!("{}", err);
+            false
+        }
+    }
+}
+
+/// How many keeps `advise` lists.
+const ADVICE_SHOWN: usize = 5;
+
+/// Ranks the ways to re-roll this hand, by points or by the chance of
+/// making one line.
+pub fn advise(words: &[&str], game: &Game) {
+    let goal = match words {
+        [_] => Goal::Points,
+        [_, slot] => match text::get_id_by_short_name(slot) {
+            Some(id) if game.scorecard().has_line(id) => Goal::Line(id),
+            _ => {
+                println!("I have no idea what this means: {}.", slot);
+                return;
+            }
+        },
+        _ => {
+            println!("Ask for points with 'advise', or for a line like 'advise ls'");
+            return;
+        }
+    };
+
+    let hand = match game.dice() {
+        Some(hand) if game.legal_actions().contains(&Action::Roll) => hand,
+        _ => {
+            println!("There are no rolls left to advise on.");
+            return;
+        }
+    };
+
+    let advice = match advisor::advise(game.card(), hand, goal) {
+        Ok(advice) => advice,
+        Err(err) => {
+            println!("Cannot advise on this hand: {}.", err);
+            return;
+        }
+    };
+    for advice in advice.iter().take(ADVICE_SHOWN) {
+        let kept: Vec<_> = advice.kept.iter().map(|f| f.to_string()).collect();
+        let rolled: Vec<_> = (1..=advice.mask.len())
+            .filter(|&p| advice.mask[p - 1])
+            .map(|p| p.to_string())
+            .collect();
+
+        let keep = if rolled.is_empty() {
+            "Keep them all".to_string()
+        } else if kept.is_empty() {
+            "Roll them all".to_string()
+        } else {
+            format!("Keep {}, 'roll {}'", kept.join(" "), rolled.join(" "))
+        };
+        match goal {
+            Goal::Points => println!("{:<32} {:>6.2} points", keep, advice.value),
+            Goal::Line(_) => println!("{:<32} {:>6.2}%", keep, advice.value * 100.0),
+        }
+    }
+}
+
+/// The exact chance of making each line with the given dice kept, over
+/// the rolls left this turn, or a whole turn between hands.
+pub fn show_odds(words: &[&str], game: &Game) {
+    let kept: Vec<_> = match words[1..].iter().map(|w| w.parse()).collect() {
+        Ok(kept) => kept,
+        Err(_) => {
+            println!("List the dice to keep, like 'odds 4 4 4'");
+            return;
+        }
+    };
+    let rolls = match game.dice() {
+        Some(hand) if hand.rolls_left > 0 => hand.rolls_left as usize,
+        _ => Dice::ROLLS_PER_TURN as usize,
+    };
+
+    match odds::chances(game.rules(), &kept, rolls) {
+        Ok(chances) => {
+            let shown: Vec<_> = kept.iter().map(|f| f.to_string()).collect();
+            let shown = if shown.is_empty() {
+                "nothing".to_string()
+            } else {
+                shown.join(" ")
+            };
+            println!("Keeping {} with {} rolls to go:", shown, rolls);
+            for (line, chance) in chances {
+                println!(
+                    "{:<16} {:>6.2}%  {}",
+                    text::get_rules_long_name(line, game.rules()),
+                    *chance.numer() as f64 / *chance.denom() as f64 * 100.0,
+                    chance
+                );
+            }
+        }
+        Err(err) => println!("{}", err),
+    }
+}
+
+/// The save a finished game is kept under, ready for `--replay`.
+const LAST_GAME: &str = "last";
+
+/// `--replay <name>` steps through a saved game instead of playing.
+fn replay_from_args() -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+
+    args.iter().position(|a| a == "--replay").map(|i| {
+        args.get(i + 1)
+            .cloned()
+            .unwrap_or_else(|| LAST_GAME.to_string())
+    })
+}
+
+/// Works out perfect play for the whole game and keeps it on disk.
+fn solve(rules: &RuleSet) {
+    let path = saves::solver_path(rules);
+    let mut solver = match Solver::load(&path, rules) {
+        Ok(solver) => solver,
+        Err(err) => {
+            println!("Cannot solve the {} rules: {}", rules.name, err);
+            return;
+        }
+    };
+
+    if solver.table_len() == 0 {
+        println!("Solving the {} rules.  This takes a while.", rules.name);
+    }
+    let expected = solver.solve_all();
+    match solver.save(&path) {
+        Ok(()) => println!("Saved the solution to {}.", path.display()),
+        Err(err) => println!("Could not save the solution: {}", err),
+    }
+    println!("Perfect play averages {:.2} points.", expected);
+}
+
 pub fn main() {
-    let mut scorecard = get_new_scorecard_data();
-    let mut hand = Dice::first_roll();
+    if let Some(name) = replay_from_args() {
+        // A file written with the 'notation' command, else a saved game.
+        if std::path::Path::new(&name).is_file() {
+            let parsed = std::fs::read_to_string(&name)
+                .map_err(|err| err.to_string())
+                .and_then(|text| notation::parse(&text).map_err(|err| err.to_string()));
+            match parsed {
+                Ok(record) => record.games.iter().for_each(replay::run),
+                Err(err) => println!("Could not read {}: {}", name, err),
+            }
+        } else {
+            match saves::load(&name) {
+                Ok(game) => replay::run(&game),
+                Err(err) => println!("Could not load {}: {}", name, err),
+            }
+        }
+        return;
+    }
+
+    let mut rules = match rules_from_args() {
+        Some(rules) => rules,
+        None => {
+            println!("Usage: --rules <{}>", RuleSet::PRESETS.join("|"));
+            return;
+        }
+    };
+
+    if std::env::args().any(|a| a == "--ordered") {
+        rules.order = PlayOrder::Strict;
+    } else if std::env::args().any(|a| a == "--ordered-upper") {
+        rules.order = PlayOrder::UpperOnly;
+    }
+
+    match std::env::args().nth(1).as_deref() {
+        Some("tournament") => return tournament::run(&rules),
+        Some("server") => return net::serve(rules),
+        Some("connect") => return net::connect(),
+        Some("scores") => return scores::show_scores(),
+        Some("stats") => return scores::show_stats(),
+        _ => {}
+    }
+
+    if std::env::args().any(|a| a == "--solve") {
+        solve(&rules);
+        return;
+    }
 
-    ui::show_card(&scorecard);
-    ui::show_hand(&hand);
+    println!("Playing by the {} rules.", rules.name);
 
-    while !scorecard.game_over() {
-        println!("Your turn.  'play', 'roll' or 'cheat' >> ");
+    let mut game = if std::env::args().any(|a| a == "--triple") {
+        Game::new_triple(rules)
+    } else {
+        Game::new(rules)
+    };
 
-        let line = read_line();
+    let mut seed = None;
+    match seed_from_args() {
+        None => {}
+        Some(Ok(n)) => {
+            println!("Rolling with seed {}.", n);
+            game.set_roller(Box::new(SeededRoller::new(n)));
+            seed = Some(n);
+        }
+        Some(Err(seed)) => {
+            println!("The seed must be a whole number, not '{}'.", seed);
+            return;
+        }
+    }
+
+    let mut bots = match bots::from_args(game.rules(), game.is_triple(), seed) {
+        Ok(bots) => bots,
+        Err(err) => {
+            println!("{}", err);
+            return;
+        }
+    };
+    if !bots.is_empty() {
+        let names: Vec<_> = bots.iter().map(|bot| bot.name()).collect();
+        println!("Playing against {}.", names.join(", "));
+    }
+
+    if let Some(names) = hotseat::names_from_args() {
+        if game.is_triple() {
+            println!("Hot-seat players get one card each; Triple is for solo games.");
+            return;
+        }
+        hotseat::run(names, game.rules().clone(), seed, bots);
+        return;
+    }
+
+    // Nothing is autosaved until the player has turned the old game down.
+    if saves::exists(saves::AUTOSAVE) {
+        match offer_resume(saves::AUTOSAVE) {
+            Resume::Saved(saved) => {
+                game = saved;
+                seed = None;
+                println!("Resumed.  Playing by the {} rules.", game.rules().name);
+            }
+            Resume::Fresh => {}
+            Resume::NoAnswer => return,
+        }
+    }
+    first_roll(&mut game);
+
+    while game.phase() != Phase::GameOver {
+        if game.legal_actions().contains(&Action::Roll) {
+            println!("Your turn.  'play', 'roll' or 'cheat' >> ");
+        } else {
+            println!("No rolls left.  'play' or 'cheat' >> ");
+        }
+
+        let line = match read_line() {
+            Some(line) => line,
+            None => {
+                autosave(&game);
+                println!("Out of input.  Type 'load {}' to resume.", saves::AUTOSAVE);
+                return;
+            }
+        };
         let words: Vec<_> = line.split_whitespace().collect();
 
         match words[0] {
             "play" => match words.as_slice() {
-                [_, slot] => {
-                    if play(&slot, &hand, &mut scorecard) {
-                        hand = Dice::first_roll();
-                        ui::show_card(&scorecard);
-                        ui::show_hand(&hand);
+                [_, slot] if !game.is_triple() => {
+                    if play(slot, 0, &mut game) {
+                        bots::take_turns(&mut bots);
+                        first_roll(&mut game);
+                        autosave(&game);
+                    }
+                }
+                [_, slot, column] if game.is_triple() => match column.parse::<usize>() {
+                    Ok(column) if column >= 1 => {
+                        if play(slot, column - 1, &mut game) {
+                            bots::take_turns(&mut bots);
+                            first_roll(&mut game);
+                            autosave(&game);
+                        }
                     }
+                    _ => println!("Columns are numbered 1 to {}.", game.card().len()),
+                },
+                _ if game.is_triple() => {
+                    println!("Play in a position and column, like 'play fh 2'");
                 }
                 _ => {
                     println!("Play in a position, like 'play fh'");
                 }
             },
             "cheat" => {
-                let dice = vec![6; 5];
-                hand = Dice::roll_fake(dice);
-                ui::show_hand(&hand);
-            }
-            "roll" => match words.len() {
-                1 => {
-                    println!("Which die positions to roll?");
-                    println!("Example 'roll 1 2 3' to re-roll the first three dice.");
+                if cheat(&mut game) {
+                    show_table(&game);
                 }
-                _ => {
-                    if hand.rolls_left == 0 {
-                        println!("No rolls left");
-                    } else {
-                        let mut reroll_flags = vec![false; hand.dice.len()];
-
-                        words[1..]
-                            .iter()
-                            .flat_map(|l| l.parse::<usize>())
-                            .map(|p| p - 1)
-                            .for_each(|p| {
-                                if let Some(flag) = reroll_flags.get_mut(p) {
-                                    *flag = true
-                                }
-                            });
-
-                        hand.reroll(&reroll_flags);
-
-                        ui::show_hand(&hand);
+                autosave(&game);
+            }
+            "save" => match words.as_slice() {
+                [_, name] => match saves::save(name, &game) {
+                    Ok(()) => println!("Saved as {}.", name),
+                    Err(err) => println!("Could not save {}: {}", name, err),
+                },
+                _ => println!("Save under a name, like 'save evening'"),
+            },
+            "load" => match words.as_slice() {
+                [_, name] => match saves::load_unfinished(name) {
+                    Ok(loaded) => {
+                        game = loaded;
+                        seed = None;
+                        println!(
+                            "Loaded {}.  Playing by the {} rules.",
+                            name,
+                            game.rules().name
+                        );
+                        first_roll(&mut game);
                     }
-                }
+                    Err(err) => println!("Could not load {}: {}", name, err),
+                },
+                _ => println!("Load a saved game by name, like 'load evening'"),
+            },
+            "advise" => advise(&words, &game),
+            "odds" => show_odds(&words, &game),
+            "notation" => {
+                let record = notation::Record {
+                    players: vec![],
+                    seed,
+                    games: vec![game.clone()],
+                };
+                print!("{}", notation::write(&record));
+            }
+            "saves" => match saves::list() {
+                Ok(ref names) if names.is_empty() => println!("There are no saved games."),
+                Ok(names) => println!("Saved games: {}", names.join(", ")),
+                Err(err) => println!("Could not list the saved games: {}", err),
             },
+            "roll" if reroll(&words, &mut game) => {
+                show_table(&game);
+                autosave(&game);
+            }
             _ => {}
         };
     }
+
+    // A finished game has nothing left to resume, but can be replayed.
+    if saves::exists(saves::AUTOSAVE) {
+        saves::remove(saves::AUTOSAVE).ok();
+    }
+    if saves::save(LAST_GAME, &game).is_ok() {
+        println!("Watch the game again with '--replay {}'.", LAST_GAME);
+    }
+
+    scores::record(&[(&scores::player_name(), &game)]);
+
+    if game.card().iter().any(|c| c.won) {
+        println!("Served on the first roll.  You win!");
+    }
+    println!("Final score: {}", game.grand_total());
+    if !bots.is_empty() {
+        bots::show_standings(&game, &bots);
+    }
 }
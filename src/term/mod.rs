@@ -1,2 +1,9 @@
+pub mod bots;
+pub mod hotseat;
 pub mod main;
+pub mod net;
+pub mod replay;
+pub mod saves;
+pub mod scores;
+pub mod tournament;
 pub mod ui;
@@ -0,0 +1,165 @@
+use super::main::{advise, line_for, read_line, reroll_mask, seed_from_args, show_odds};
+use super::scores;
+use super::tournament::value_of;
+use super::ui;
+use rust_dice5::engine::hotseat::HotSeat;
+use rust_dice5::engine::net::{Client, Reply, Request, Server};
+use rust_dice5::engine::RuleSet;
+
+const DEFAULT_PORT: u16 = 5055;
+const DEFAULT_PLAYERS: usize = 2;
+
+/// `server [--port n] [--players n] [--seed n]` hosts one game and shows
+/// the standings when it is over.
+pub fn serve(rules: RuleSet) {
+    let args: Vec<String> = std::env::args().skip(2).collect();
+    let port = value_of(&args, "--port", DEFAULT_PORT);
+    let players = value_of(&args, "--players", DEFAULT_PLAYERS);
+    let (port, players) = match (port, players) {
+        (Ok(port), Ok(players)) => (port, players),
+        (Err(err), _) | (_, Err(err)) => {
+            println!("{}", err);
+            return;
+        }
+    };
+
+    let name = rules.name.clone();
+    let mut server = match Server::bind(("0.0.0.0", port), rules, players) {
+        Ok(server) => server,
+        Err(err) => {
+            println!("Cannot host on port {}: {}", port, err);
+            return;
+        }
+    };
+    match seed_from_args() {
+        Some(Ok(seed)) => server.set_seed(seed),
+        Some(Err(err)) => {
+            println!("{}", err);
+            return;
+        }
+        None => {}
+    }
+
+    println!(
+        "Hosting a {}-player game of the {} rules on port {}.",
+        players, name, port
+    );
+    println!("Players join with 'connect <this host>:{}'.", port);
+    match server.run() {
+        Ok(table) => {
+            ui::show_hotseat_card(&table);
+            ui::show_standings(&table.standings());
+        }
+        Err(err) => println!("The game stopped: {}", err),
+    }
+}
+
+/// `connect <host:port> [--name n]` joins a game hosted with `server`.
+pub fn connect() {
+    let args: Vec<String> = std::env::args().skip(2).collect();
+    let addr = match args.first() {
+        Some(addr) if !addr.starts_with("--") => addr.clone(),
+        _ => {
+            println!(
+                "Name the server to join, like 'connect lunch.local:{}'.",
+                DEFAULT_PORT
+            );
+            return;
+        }
+    };
+    let name = args
+        .iter()
+        .position(|a| a == "--name")
+        .and_then(|i| args.get(i + 1).cloned())
+        .or_else(|| std::env::var("USER").ok())
+        .unwrap_or_default();
+
+    let mut client = match Client::connect(&addr, &name) {
+        Ok(client) => client,
+        Err(err) => {
+            println!("Cannot join {}: {}", addr, err);
+            return;
+        }
+    };
+    println!("Joined {}.  Waiting for everyone to arrive.", addr);
+
+    let mut table: Option<HotSeat> = None;
+    loop {
+        match client.receive() {
+            Ok(Reply::Table { table: seat }) => {
+                ui::show_hotseat_card(&seat);
+                if seat.is_over() {
+                    let me = &seat.players()[client.seat()];
+                    scores::record(&[(&me.name, &me.game)]);
+                    ui::show_standings(&seat.standings());
+                    return;
+                }
+                if seat.turn() == client.seat() {
+                    if let Some(hand) = seat.current().game.dice() {
+                        ui::show_hand(hand);
+                    }
+                } else {
+                    println!("Waiting for {}.", seat.current().name);
+                }
+                table = Some(seat);
+            }
+            Ok(Reply::Refused { reason }) => println!("The server refused: {}.", reason),
+            Ok(Reply::Left { name }) => {
+                println!("{} left, so the game is over.", name);
+                return;
+            }
+            Ok(Reply::Welcome { .. }) => {}
+            Err(err) => {
+                println!("Lost the server: {}", err);
+                return;
+            }
+        }
+
+        let seat = match &table {
+            Some(seat) if seat.turn() == client.seat() => seat,
+            _ => continue,
+        };
+        let sent = match next_request(seat) {
+            Some(request) => client.send(&request),
+            None => {
+                println!("Out of input.  Leaving the game.");
+                return;
+            }
+        };
+        if let Err(err) = sent {
+            println!("Lost the server: {}", err);
+            return;
+        }
+    }
+}
+
+/// Reads commands until one is for the server.  Advice and odds are worked
+/// out here, from the copy of the table.
+fn next_request(seat: &HotSeat) -> Option<Request> {
+    let game = &seat.current().game;
+    loop {
+        println!("Your turn.  'play' or 'roll' >> ");
+        let line = read_line()?;
+        let words: Vec<_> = line.split_whitespace().collect();
+
+        match words[0] {
+            "play" => match words.as_slice() {
+                [_, slot] => {
+                    if let Some(line) = line_for(slot, game) {
+                        return Some(Request::Score { line });
+                    }
+                }
+                _ => println!("Play in a position, like 'play fh'"),
+            },
+            "roll" => {
+                if let Some(mask) = reroll_mask(&words, game) {
+                    return Some(Request::Roll { mask });
+                }
+            }
+            "advise" => advise(&words, game),
+            "odds" => show_odds(&words, game),
+            "cheat" => println!("The server rolls the dice in a network game."),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use super::main::{read_line, show_card};
+use super::ui;
+use rust_dice5::engine::{Event, Game};
+use rust_dice5::text;
+
+pub fn describe(event: &Event, game: &Game) -> String {
+    match event {
+        Event::Roll { mask, dice } if mask.is_empty() => {
+            format!("First roll: {}", dice_text(dice))
+        }
+        Event::Roll { mask, dice } => {
+            let positions: Vec<_> = mask
+                .iter()
+                .enumerate()
+                .filter(|&(_, &flag)| flag)
+                .map(|(p, _)| (p + 1).to_string())
+                .collect();
+            format!("Re-rolled {}: {}", positions.join(" "), dice_text(dice))
+        }
+        Event::SetDice { dice } => format!("Cheated: {}", dice),
+        Event::Score {
+            column,
+            line,
+            points,
+        } => {
+            let long_name = text::get_rules_long_name(*line, game.rules());
+            if game.is_triple() {
+                format!(
+                    "Played {} points on {} in column {}",
+                    points,
+                    long_name,
+                    column + 1
+                )
+            } else {
+                format!("Played {} points on {}", points, long_name)
+            }
+        }
+    }
+}
+
+fn dice_text(dice: &[i8]) -> String {
+    let faces: Vec<_> = dice.iter().map(|f| f.to_string()).collect();
+    faces.join(" ")
+}
+
+fn show_step(steps: &[Game], log: &[Event], at: usize) {
+    let game = &steps[at];
+
+    show_card(game);
+    if let Some(hand) = game.dice() {
+        ui::show_hand(hand);
+    }
+    match at {
+        0 => println!("Step 0 of {}: the empty card.", log.len()),
+        _ => println!(
+            "Step {} of {}: {}",
+            at,
+            log.len(),
+            describe(&log[at - 1], game)
+        ),
+    }
+}
+
+/// Steps through a saved game, forward and backward.
+pub fn run(game: &Game) {
+    let steps = match game.replay() {
+        Ok(steps) => steps,
+        Err(err) => {
+            println!("This game cannot be replayed: {}", err);
+            return;
+        }
+    };
+    let last = steps.len() - 1;
+    let mut at = 0;
+
+    show_step(&steps, game.log(), at);
+
+    loop {
+        println!("'next', 'back', 'start', 'end' or 'quit' >> ");
+
+        let line = match read_line() {
+            Some(line) => line,
+            None => return,
+        };
+
+        at = match line.trim() {
+            "next" | "n" if at < last => at + 1,
+            "back" | "b" if at > 0 => at - 1,
+            "start" => 0,
+            "end" => last,
+            "quit" | "q" => return,
+            "next" | "n" => {
+                println!("That was the last step.");
+                continue;
+            }
+            "back" | "b" => {
+                println!("This is the first step.");
+                continue;
+            }
+            _ => continue,
+        };
+        show_step(&steps, game.log(), at);
+    }
+}
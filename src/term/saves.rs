@@ -0,0 +1,167 @@
+use rust_dice5::engine::hotseat::{HotSeat, MAX_PLAYERS, MIN_PLAYERS};
+use rust_dice5::engine::{Game, GameError, Phase, RuleSet};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// The save written after every move, so an interrupted game can resume.
+pub const AUTOSAVE: &str = "autosave";
+
+/// The same for a hot-seat game, kept apart so a solo game never trips
+/// over it.
+pub const AUTOSAVE_HOTSEAT: &str = "autosave-hotseat";
+
+const EXTENSION: &str = "json";
+
+/// What the terminal loads, checked first so an edited or damaged save is
+/// refused rather than played.
+pub trait Save: DeserializeOwned {
+    /// Why the save cannot be played, if it cannot.
+    fn check(&self) -> Result<(), String>;
+
+    fn is_over(&self) -> bool;
+}
+
+impl Save for Game {
+    /// Replaying the log checks every move and the dice on the table.
+    fn check(&self) -> Result<(), String> {
+        if self.card().is_empty() {
+            return Err("there is no card".to_string());
+        }
+        self.replay().map(|_| ()).map_err(|err| err.to_string())
+    }
+
+    fn is_over(&self) -> bool {
+        self.phase() == Phase::GameOver
+    }
+}
+
+impl Save for HotSeat {
+    fn check(&self) -> Result<(), String> {
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&self.players().len()) {
+            return Err(GameError::PlayerCount.to_string());
+        }
+        if self.turn() >= self.players().len() {
+            return Err("it is nobody's turn".to_string());
+        }
+        self.players().iter().try_for_each(|p| p.game.check())
+    }
+
+    fn is_over(&self) -> bool {
+        HotSeat::is_over(self)
+    }
+}
+
+/// `.rust_dice5` in the home folder.
+fn data_dir() -> PathBuf {
+    let home = std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map_or_else(PathBuf::new, PathBuf::from);
+    home.join(".rust_dice5")
+}
+
+/// Saves live in `$DICE5_SAVES`, or `.rust_dice5/saves` in the home folder.
+fn save_dir() -> PathBuf {
+    match std::env::var_os("DICE5_SAVES") {
+        Some(dir) => PathBuf::from(dir),
+        None => data_dir().join("saves"),
+    }
+}
+
+/// Where the solver keeps its table for a rule set.
+pub fn solver_path(rules: &RuleSet) -> PathBuf {
+    let order = format!("{:?}", rules.order).to_lowercase();
+    data_dir()
+        .join("solver")
+        .join(format!("{}-{}.bin", rules.name, order))
+}
+
+/// Every finished game, one JSON entry per line.
+pub fn scores_path() -> PathBuf {
+    data_dir().join("scores.jsonl")
+}
+
+/// Save names become file names, so only plain names are allowed.
+fn save_path(name: &str) -> io::Result<PathBuf> {
+    let plain = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    if !plain {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "save names may only use letters, digits, '-' and '_'",
+        ));
+    }
+    Ok(save_dir().join(name).with_extension(EXTENSION))
+}
+
+/// Saves a game, or anything else the terminal keeps, such as a hot-seat
+/// table.
+pub fn save<T: Serialize>(name: &str, game: &T) -> io::Result<()> {
+    let path = save_path(name)?;
+    fs::create_dir_all(save_dir())?;
+
+    // Write then rename, so a crash mid-write never leaves half a save.
+    let temp = path.with_extension("tmp");
+    fs::write(&temp, serde_json::to_string(game)?)?;
+    fs::rename(temp, path)
+}
+
+/// Loads a save, refusing one that does not hold up.
+pub fn load<T: Save>(name: &str) -> io::Result<T> {
+    let json = fs::read_to_string(save_path(name)?)?;
+    let saved: T = serde_json::from_str(&json)?;
+    saved.check().map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("the save is damaged: {}", err),
+        )
+    })?;
+    Ok(saved)
+}
+
+/// Loads a save to play on, refusing a finished game, which would only be
+/// over again straight away.  `--replay` shows finished games.
+pub fn load_unfinished<T: Save>(name: &str) -> io::Result<T> {
+    let saved: T = load(name)?;
+    if saved.is_over() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "that game is over",
+        ));
+    }
+    Ok(saved)
+}
+
+pub fn exists(name: &str) -> bool {
+    save_path(name).is_ok_and(|path| path.is_file())
+}
+
+pub fn remove(name: &str) -> io::Result<()> {
+    fs::remove_file(save_path(name)?)
+}
+
+/// The names of every save, sorted.
+pub fn list() -> io::Result<Vec<String>> {
+    let entries = match fs::read_dir(save_dir()) {
+        Ok(entries) => entries,
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err),
+    };
+
+    let mut names = vec![];
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == EXTENSION) {
+            if let Some(stem) = path.file_stem() {
+                names.push(stem.to_string_lossy().into_owned());
+            }
+        }
+    }
+    names.sort();
+    Ok(names)
+}
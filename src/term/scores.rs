@@ -0,0 +1,191 @@
+use super::saves;
+use super::tournament::value_of;
+use rust_dice5::engine::scores::{self, Entry};
+use rust_dice5::engine::Game;
+use rust_dice5::text;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const DEFAULT_TOP: usize = 10;
+
+/// Flags that take a value, so the value is not read as a player's name.
+const VALUE_FLAGS: [&str; 2] = ["--rules", "--top"];
+
+/// The name games are recorded under: `--name`, or the login name.
+pub fn player_name() -> String {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter()
+        .position(|a| a == "--name")
+        .and_then(|i| args.get(i + 1).cloned())
+        .or_else(|| std::env::var("USER").ok())
+        .or_else(|| std::env::var("USERNAME").ok())
+        .unwrap_or_else(|| "You".to_string())
+}
+
+/// Adds finished games to the score table.
+pub fn record(games: &[(&str, &Game)]) {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+
+    for &(player, game) in games {
+        let entry = Entry::new(player, now, game);
+        if let Err(err) = scores::append(&saves::scores_path(), &entry) {
+            println!("Could not record the score: {}", err);
+            return;
+        }
+    }
+}
+
+/// `YYYY-MM-DD` in UTC for seconds since the Unix epoch.
+fn date(seconds: u64) -> String {
+    // Howard Hinnant's days-to-civil algorithm.
+    let days = (seconds / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// The recorded games for the player and `--rules` named on the command
+/// line, or every game.  Prints why when there are none.
+fn matching<'a>(args: &[String], entries: &'a [Entry]) -> Vec<&'a Entry> {
+    let player = args
+        .iter()
+        .enumerate()
+        .find(|&(i, a)| {
+            !a.starts_with("--") && (i == 0 || !VALUE_FLAGS.contains(&args[i - 1].as_str()))
+        })
+        .map(|(_, a)| a.as_str());
+    let rules = args
+        .iter()
+        .position(|a| a == "--rules")
+        .and_then(|i| args.get(i + 1))
+        .map(String::as_str);
+
+    let found: Vec<&Entry> = entries
+        .iter()
+        .filter(|e| player.is_none_or(|p| e.player == p))
+        .filter(|e| rules.is_none_or(|r| e.rules == r))
+        .collect();
+    if entries.is_empty() {
+        println!("No finished games have been recorded yet.");
+    } else if found.is_empty() {
+        println!("No recorded games match.");
+    }
+    found
+}
+
+/// Explains an empty table when every matching game was cheated.
+fn no_fair_games(found: &[&Entry]) {
+    if !found.is_empty() {
+        println!("The dice were set by hand in every game that matches.");
+    }
+}
+
+fn load() -> Option<Vec<Entry>> {
+    match scores::load(&saves::scores_path()) {
+        Ok(entries) => Some(entries),
+        Err(err) => {
+            println!("Could not read the scores: {}", err);
+            None
+        }
+    }
+}
+
+/// `scores [--top n] [--rules r] [player]` shows the best games.
+pub fn show_scores() {
+    let args: Vec<String> = std::env::args().skip(2).collect();
+    let n = match value_of(&args, "--top", DEFAULT_TOP) {
+        Ok(n) => n,
+        Err(err) => {
+            println!("{}", err);
+            return;
+        }
+    };
+    let entries = match load() {
+        Some(entries) => entries,
+        None => return,
+    };
+    let found = matching(&args, &entries);
+    let best = scores::top(&found, n);
+    if best.is_empty() {
+        no_fair_games(&found);
+        return;
+    }
+
+    println!(
+        "{:>3}  {:<12} {:>5}  {:<10}  Rules",
+        "", "Player", "Total", "Date"
+    );
+    for (place, entry) in best.iter().enumerate() {
+        let total = if entry.won {
+            "won".to_string()
+        } else {
+            entry.total.to_string()
+        };
+        println!(
+            "{:>3}  {:<12} {:>5}  {:<10}  {}",
+            place + 1,
+            entry.player,
+            total,
+            date(entry.finished),
+            entry.rules
+        );
+    }
+}
+
+/// `stats [--rules r] [player]` sums up the recorded games.
+pub fn show_stats() {
+    let args: Vec<String> = std::env::args().skip(2).collect();
+    let entries = match load() {
+        Some(entries) => entries,
+        None => return,
+    };
+    let found = matching(&args, &entries);
+    let stats = match scores::stats(&found) {
+        Some(stats) => stats,
+        None => {
+            no_fair_games(&found);
+            return;
+        }
+    };
+
+    println!("{:<16}{:>8}", "Games", stats.games);
+    println!("{:<16}{:>8.1}", "Average", stats.mean);
+    println!("{:<16}{:>8}", "Best", stats.best);
+    println!("{:<16}{:>8}", "Worst", stats.worst);
+    println!(
+        "{:<16}{:>7.1}%",
+        "Upper bonus",
+        stats.upper_bonus_rate * 100.0
+    );
+    println!("{:<16}{:>7.1}%", "Dice5", stats.dice5_rate * 100.0);
+
+    println!();
+    println!("Bonus Dice5s per game:");
+    for (bonuses, &games) in stats.bonus_dice5s.iter().enumerate() {
+        if games > 0 {
+            println!(
+                "{:>4} {:>7.1}%",
+                bonuses,
+                games as f64 * 100.0 / stats.games as f64
+            );
+        }
+    }
+
+    println!();
+    println!("Average points per line:");
+    for (line, points) in &stats.lines {
+        println!("{:<16}{:>8.2}", text::get_long_name(*line), points);
+    }
+}
@@ -0,0 +1,135 @@
+use super::bots;
+use rust_dice5::engine::strategy::Strategy;
+use rust_dice5::engine::tournament;
+use rust_dice5::engine::{RuleSet, SeededRoller};
+use rust_dice5::text;
+
+const DEFAULT_GAMES: usize = 1000;
+const DEFAULT_SEED: u64 = 1;
+
+/// Flags that take a value, so the value is not read as a bot name.
+const VALUE_FLAGS: [&str; 3] = ["--rules", "--games", "--seed"];
+
+/// The value after `flag`, or `default` without the flag.
+pub fn value_of<T: std::str::FromStr>(
+    args: &[String],
+    flag: &str,
+    default: T,
+) -> Result<T, String> {
+    match args.iter().position(|a| a == flag) {
+        None => Ok(default),
+        Some(i) => {
+            let value = args.get(i + 1).cloned().unwrap_or_default();
+            value
+                .parse()
+                .map_err(|_| format!("{} needs a whole number, not '{}'.", flag, value))
+        }
+    }
+}
+
+/// `tournament [--games n] [--seed n] <bot> <bot>...` plays the bots
+/// against each other on the same dice and compares them.
+pub fn run(rules: &RuleSet) {
+    let args: Vec<String> = std::env::args().skip(2).collect();
+    let games = value_of(&args, "--games", DEFAULT_GAMES);
+    let seed = value_of(&args, "--seed", DEFAULT_SEED);
+    let (games, seed) = match (games, seed) {
+        (Ok(games), Ok(seed)) => (games, seed),
+        (Err(err), _) | (_, Err(err)) => {
+            println!("{}", err);
+            return;
+        }
+    };
+
+    let names: Vec<&String> = args
+        .iter()
+        .enumerate()
+        .filter(|&(i, a)| {
+            !a.starts_with("--") && (i == 0 || !VALUE_FLAGS.contains(&args[i - 1].as_str()))
+        })
+        .map(|(_, a)| a)
+        .collect();
+    if names.is_empty() {
+        println!(
+            "Name the bots to play, like 'tournament greedy heuristic'.  Bots: {}",
+            bots::NAMES.join(", ")
+        );
+        return;
+    }
+
+    let mut strategies: Vec<Box<dyn Strategy>> = vec![];
+    for (n, name) in names.iter().enumerate() {
+        // Dice for the random bot's choices, apart from the game dice.
+        let roller = Box::new(SeededRoller::new(seed.wrapping_sub(n as u64 + 1)));
+        match bots::strategy_for(name, rules, false, roller) {
+            Ok(strategy) => strategies.push(strategy),
+            Err(err) => {
+                println!("{}", err);
+                return;
+            }
+        }
+    }
+    println!(
+        "Playing {} games of the {} rules from seed {}.",
+        games, rules.name, seed
+    );
+    let report = match tournament::run(rules, &mut strategies, games, seed) {
+        Ok(report) => report,
+        Err(err) => {
+            println!("The tournament stopped: {}", err);
+            return;
+        }
+    };
+    show(&report, rules);
+}
+
+fn show(report: &tournament::Report, rules: &RuleSet) {
+    let versus = report.baseline.map_or(String::new(), |b| {
+        format!("vs {}", report.standings[b].name)
+    });
+    println!();
+    println!(
+        "{:<10} {:>7} {:>7} {:>17} {:>6}   {}",
+        "Bot", "Mean", "Std dev", "95% interval", "Wins", versus
+    );
+    for (s, standing) in report.standings.iter().enumerate() {
+        if let Some(err) = &standing.error {
+            println!("{:<10} invalid: {}", standing.name, err);
+            continue;
+        }
+        let (low, high) = standing.interval;
+        let versus = if Some(s) == report.baseline {
+            String::new()
+        } else {
+            let (difference, (low, high)) = standing.difference;
+            format!("{:+.1} ({:+.1} to {:+.1})", difference, low, high)
+        };
+        println!(
+            "{:<10} {:>7.1} {:>7.1} {:>8.1} to {:>5.1} {:>5.1}%   {}",
+            standing.name,
+            standing.mean,
+            standing.std_dev,
+            low,
+            high,
+            standing.win_rate * 100.0,
+            versus
+        );
+    }
+
+    println!();
+    print!("{:<16}", "Line");
+    for standing in &report.standings {
+        print!(" {:>10}", standing.name);
+    }
+    println!();
+    for (l, &(line, _)) in report.standings[0].lines.iter().enumerate() {
+        print!("{:<16}", text::get_rules_long_name(line, rules));
+        for standing in &report.standings {
+            match standing.error {
+                Some(_) => print!(" {:>10}", "-"),
+                None => print!(" {:>10.2}", standing.lines[l].1),
+            }
+        }
+        println!();
+    }
+}
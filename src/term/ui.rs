@@ -11,29 +11,101 @@
 
 // use term_painter::Attr::*;
 // use term_painter::{Color, ToStyle};
-use crate::engine::{Dice, DieFace, LineId, ScoreCardData, SubtotalData};
-use crate::text;
+use rust_dice5::engine::hotseat::{HotSeat, Standing};
+use rust_dice5::engine::triple;
+use rust_dice5::engine::{Dice, DieFace, LineId, ScoreCardData};
+use rust_dice5::text;
 use term_painter::Color::*;
 use term_painter::ToStyle;
 
 const LONG_NAME_WIDTH: usize = 15;
 const SCORE_BOX_WIDTH: usize = 5;
+const PLAYER_COLUMN_WIDTH: usize = 8;
 
-pub fn print_line(score_card: &ScoreCardData, id: LineId) {
-    let line = score_card.get_line_by_id(id);
+/// The points each open line would score with the dice on the table.
+type Preview = [(LineId, i16)];
+
+fn preview_for(score_card: &ScoreCardData, hand: Option<&Dice>) -> Vec<(LineId, i16)> {
+    hand.map_or_else(Vec::new, |hand| score_card.preview(hand))
+}
+
+pub fn print_line(score_card: &ScoreCardData, id: LineId, preview: &Preview) {
     print!(
         "{:width$}  ",
-        White.bg(Black).paint(text::get_long_name(line.id)),
+        White
+            .bg(Black)
+            .paint(text::get_rules_long_name(id, &score_card.rules)),
         width = LONG_NAME_WIDTH,
     );
-    if let Some(val) = line.value {
+    print_value(score_card, id, preview);
+}
+
+pub fn print_subtotal(id: LineId, score_card: &ScoreCardData) {
+    let val = score_card.subtotal(id).unwrap_or(0);
+
+    print!(
+        "{:width$}",
+        White
+            .bg(Black)
+            .paint(text::get_rules_long_name(id, &score_card.rules)),
+        width = LONG_NAME_WIDTH,
+    );
+
+    print!("  ");
+
+    print!(
+        "{:width$}",
+        Cyan.bg(Black).bold().paint(&val),
+        width = SCORE_BOX_WIDTH,
+    );
+    println!();
+}
+
+/// Draws the card; with `hand`, open lines show what it would score.
+pub fn show_card(score_card: &ScoreCardData, hand: Option<&Dice>) {
+    let preview = preview_for(score_card, hand);
+
+    for line in score_card.line.iter().filter(|l| l.id.is_upper()) {
+        print_line(score_card, line.id, &preview);
+        println!();
+    }
+    print_subtotal(LineId::UpperSubtotal, score_card);
+    print_subtotal(LineId::UpperBonus, score_card);
+    print_subtotal(LineId::UpperTotal, score_card);
+    println!("-------------------------");
+
+    for line in score_card.line.iter().filter(|l| !l.id.is_upper()) {
+        print_line(score_card, line.id, &preview);
+        println!();
+    }
+    print_subtotal(LineId::BottomSubtotal, score_card);
+    println!("-------------------------");
+
+    print_subtotal(LineId::Dice5Bonus, score_card);
+    println!("-------------------------");
+
+    print_subtotal(LineId::GrandTotal, score_card);
+    println!("-------------------------");
+}
+
+fn print_value(score_card: &ScoreCardData, id: LineId, preview: &Preview) {
+    let value = score_card.get_line_by_id(id).ok().and_then(|l| l.value);
+    let would_score = preview.iter().find(|&&(l, _)| l == id).map(|&(_, p)| p);
+
+    if let Some(val) = value {
         print!(
             "{:>width$}",
             Cyan.bg(Black).bold().paint(val),
             width = SCORE_BOX_WIDTH,
         );
+    } else if let Some(points) = would_score {
+        print!(
+            "{:>width$}",
+            Green.bg(Black).paint(points),
+            width = SCORE_BOX_WIDTH,
+        );
     } else {
-        let short = format!("<{}>", text::get_short_name(line.id));
+        let short = format!("<{}>", text::get_short_name(id));
         print!(
             "{:width$}",
             Yellow.bg(Black).bold().paint(short),
@@ -42,65 +114,210 @@ pub fn print_line(score_card: &ScoreCardData, id: LineId) {
     }
 }
 
-pub fn print_subtotal(line: &SubtotalData, score_card: &ScoreCardData) {
-    let val = (line.calc)(&score_card);
-
+fn print_triple_line(card: &[ScoreCardData], id: LineId, previews: &[Vec<(LineId, i16)>]) {
     print!(
         "{:width$}",
-        White.bg(Black).paint(text::get_long_name(line.id)),
+        White
+            .bg(Black)
+            .paint(text::get_rules_long_name(id, &card[0].rules)),
         width = LONG_NAME_WIDTH,
     );
+    for (score_card, preview) in card.iter().zip(previews) {
+        print!("  ");
+        print_value(score_card, id, preview);
+    }
+    println!();
+}
 
-    print!("  ");
-
+fn print_triple_subtotal(card: &[ScoreCardData], id: LineId) {
     print!(
         "{:width$}",
-        Cyan.bg(Black).bold().paint(&val),
-        width = SCORE_BOX_WIDTH,
+        White
+            .bg(Black)
+            .paint(text::get_rules_long_name(id, &card[0].rules)),
+        width = LONG_NAME_WIDTH,
     );
+    for score_card in card {
+        let val = score_card.subtotal(id).unwrap_or(0);
+        print!("  ");
+        print!(
+            "{:>width$}",
+            Cyan.bg(Black).bold().paint(&val),
+            width = SCORE_BOX_WIDTH,
+        );
+    }
     println!();
 }
 
-pub fn show_card(score_card: &ScoreCardData) {
-    print_line(score_card, LineId::Ace);
-    println!();
-    print_line(score_card, LineId::Two);
-    println!();
-    print_line(score_card, LineId::Three);
-    println!();
-    print_line(score_card, LineId::Four);
-    println!();
-    print_line(score_card, LineId::Five);
-    println!();
-    print_line(score_card, LineId::Six);
-    println!();
-    print_subtotal(&score_card.calc_upper_subtotal, &score_card);
-    print_subtotal(&score_card.calc_upper_bonus, &score_card);
-    print_subtotal(&score_card.calc_upper_total, &score_card);
-    println!("-------------------------");
+pub fn show_triple_card(card: &[ScoreCardData], hand: Option<&Dice>) {
+    let previews: Vec<_> = card.iter().map(|c| preview_for(c, hand)).collect();
 
-    print_line(score_card, LineId::ThreeKind);
-    println!();
-    print_line(score_card, LineId::FourKind);
-    println!();
-    print_line(score_card, LineId::SmallStraight);
+    print!("{:width$}", "", width = LONG_NAME_WIDTH);
+    for column in 0..card.len() {
+        let header = format!("x{}", triple::multiplier(column));
+        print!("  {:>width$}", header, width = SCORE_BOX_WIDTH);
+    }
     println!();
-    print_line(score_card, LineId::LargeStraight);
+
+    for line in card[0].line.iter().filter(|l| l.id.is_upper()) {
+        print_triple_line(card, line.id, &previews);
+    }
+    print_triple_subtotal(card, LineId::UpperSubtotal);
+    print_triple_subtotal(card, LineId::UpperBonus);
+    print_triple_subtotal(card, LineId::UpperTotal);
+    println!("---------------------------------------");
+
+    for line in card[0].line.iter().filter(|l| !l.id.is_upper()) {
+        print_triple_line(card, line.id, &previews);
+    }
+    print_triple_subtotal(card, LineId::BottomSubtotal);
+    println!("---------------------------------------");
+
+    print_triple_subtotal(card, LineId::Dice5Bonus);
+    print_triple_subtotal(card, LineId::GrandTotal);
+    println!("---------------------------------------");
+
+    print!("{:width$}", "Multiplied", width = LONG_NAME_WIDTH);
+    for column in 0..card.len() {
+        let val = triple::column_total(card, column);
+        print!("  ");
+        print!(
+            "{:>width$}",
+            Cyan.bg(Black).bold().paint(&val),
+            width = SCORE_BOX_WIDTH,
+        );
+    }
     println!();
-    print_line(score_card, LineId::FullHouse);
+
+    print!("{:width$}", "Triple Total", width = LONG_NAME_WIDTH);
+    print!("  ");
+    println!(
+        "{:>width$}",
+        Cyan.bg(Black).bold().paint(triple::grand_total(card)),
+        width = SCORE_BOX_WIDTH,
+    );
+    println!("---------------------------------------");
+}
+
+fn print_player_gap() {
+    print!(
+        "{:width$}",
+        "",
+        width = PLAYER_COLUMN_WIDTH - SCORE_BOX_WIDTH + 1
+    );
+}
+
+fn print_hotseat_line(seat: &HotSeat, id: LineId, preview: &Preview) {
+    let rules = seat.current().game.rules();
+    print!(
+        "{:width$}",
+        White.bg(Black).paint(text::get_rules_long_name(id, rules)),
+        width = LONG_NAME_WIDTH,
+    );
+    for (i, player) in seat.players().iter().enumerate() {
+        let score_card = player.game.scorecard();
+        print_player_gap();
+        if i == seat.turn() {
+            print_value(score_card, id, preview);
+        } else {
+            let value = score_card.get_line_by_id(id).ok().and_then(|l| l.value);
+            let value = value.map_or_else(String::new, |v| v.to_string());
+            print!(
+                "{:>width$}",
+                Cyan.bg(Black).bold().paint(value),
+                width = SCORE_BOX_WIDTH,
+            );
+        }
+    }
     println!();
-    print_line(score_card, LineId::Chance);
+}
+
+fn print_hotseat_subtotal(seat: &HotSeat, id: LineId) {
+    let rules = seat.current().game.rules();
+    print!(
+        "{:width$}",
+        White.bg(Black).paint(text::get_rules_long_name(id, rules)),
+        width = LONG_NAME_WIDTH,
+    );
+    for player in seat.players() {
+        let val = player.game.scorecard().subtotal(id).unwrap_or(0);
+        print_player_gap();
+        print!(
+            "{:>width$}",
+            Cyan.bg(Black).bold().paint(&val),
+            width = SCORE_BOX_WIDTH,
+        );
+    }
     println!();
-    print_line(score_card, LineId::Dice5);
+}
+
+/// Every player's card side by side.  The player whose turn it is has
+/// their name highlighted, and their column shows what the dice would
+/// score.
+pub fn show_hotseat_card(seat: &HotSeat) {
+    let current = &seat.current().game;
+    let preview = preview_for(current.scorecard(), current.dice());
+    let rule = "-".repeat(LONG_NAME_WIDTH + (PLAYER_COLUMN_WIDTH + 1) * seat.players().len());
+
+    print!("{:width$}", "", width = LONG_NAME_WIDTH);
+    for (i, player) in seat.players().iter().enumerate() {
+        let name: String = player.name.chars().take(PLAYER_COLUMN_WIDTH).collect();
+        print!(" ");
+        if i == seat.turn() {
+            print!(
+                "{:>width$}",
+                Black.bg(Yellow).bold().paint(name),
+                width = PLAYER_COLUMN_WIDTH,
+            );
+        } else {
+            print!("{:>width$}", name, width = PLAYER_COLUMN_WIDTH);
+        }
+    }
     println!();
-    print_subtotal(&score_card.calc_lower_subtotal, &score_card);
-    println!("-------------------------");
 
-    print_subtotal(&score_card.calc_dice5_bonus, &score_card);
-    println!("-------------------------");
+    let lines = &current.scorecard().line;
+    for line in lines.iter().filter(|l| l.id.is_upper()) {
+        print_hotseat_line(seat, line.id, &preview);
+    }
+    print_hotseat_subtotal(seat, LineId::UpperSubtotal);
+    print_hotseat_subtotal(seat, LineId::UpperBonus);
+    print_hotseat_subtotal(seat, LineId::UpperTotal);
+    println!("{}", rule);
 
-    print_subtotal(&score_card.calc_grand_total, &score_card);
-    println!("-------------------------");
+    for line in lines.iter().filter(|l| !l.id.is_upper()) {
+        print_hotseat_line(seat, line.id, &preview);
+    }
+    print_hotseat_subtotal(seat, LineId::BottomSubtotal);
+    println!("{}", rule);
+
+    print_hotseat_subtotal(seat, LineId::Dice5Bonus);
+    print_hotseat_subtotal(seat, LineId::GrandTotal);
+    println!("{}", rule);
+}
+
+/// The final ranking.  Players on the same total share a place.
+pub fn show_standings(standings: &[Standing]) {
+    for standing in standings {
+        let tied = standings
+            .iter()
+            .filter(|s| s.place == standing.place)
+            .count()
+            > 1;
+        let place = if tied {
+            format!("={}", standing.place)
+        } else {
+            standing.place.to_string()
+        };
+
+        if standing.won {
+            println!(
+                "{:>3}  {:<12} served a Dice5 and wins",
+                place, standing.name
+            );
+        } else {
+            println!("{:>3}  {:<12} {:>5}", place, standing.name, standing.total);
+        }
+    }
 }
 
 pub fn show_hand(hand: &Dice) {
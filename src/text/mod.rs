@@ -1,4 +1,4 @@
-use super::engine::LineId;
+use crate::engine::{LineId, RuleSet, Scoring};
 
 pub fn get_long_name(zid: LineId) -> String {
     match zid {
@@ -12,19 +12,46 @@ pub fn get_long_name(zid: LineId) -> String {
         LineId::UpperBonus => ">63 bonus".to_string(),
         LineId::UpperTotal => "Total".to_string(),
 
+        LineId::OnePair => "One Pair".to_string(),
+        LineId::TwoPairs => "Two Pairs".to_string(),
+        LineId::ThreePairs => "Three Pairs".to_string(),
         LineId::ThreeKind => "3 Kind".to_string(),
         LineId::FourKind => "4 Kind".to_string(),
         LineId::SmallStraight => "Small Straight".to_string(),
         LineId::LargeStraight => "Large Straight".to_string(),
+        LineId::FullStraight => "Full Straight".to_string(),
         LineId::FullHouse => "Full House".to_string(),
+        LineId::Castle => "Castle".to_string(),
+        LineId::Tower => "Tower".to_string(),
         LineId::Chance => "Change".to_string(),
         LineId::Dice5 => "Dice 5".to_string(),
+        LineId::DoubleDice5 => "Double Dice 5".to_string(),
         LineId::BottomSubtotal => "Subtotal".to_string(),
         LineId::Dice5Bonus => "Dice5 Bonus".to_string(),
         LineId::GrandTotal => "Grand Total".to_string(),
     }
 }
 
+/// The name printed on the card for the given rules, which may differ
+/// from the Dice5 name (a Dice5 is a "Yacht" in Yacht).
+pub fn get_rules_long_name(zid: LineId, rules: &RuleSet) -> String {
+    match (rules.scoring, zid) {
+        (_, LineId::UpperBonus) => format!(">{} bonus", rules.upper_bonus_threshold),
+        (Scoring::Yatzy, LineId::Dice5) => "Yatzy".to_string(),
+        (Scoring::Maxi, LineId::Dice5) => "Maxi Yatzy".to_string(),
+        (Scoring::Yacht, LineId::SmallStraight) => "Little Straight".to_string(),
+        (Scoring::Yacht, LineId::LargeStraight) => "Big Straight".to_string(),
+        (Scoring::Yacht, LineId::Chance) => "Choice".to_string(),
+        (Scoring::Yacht, LineId::Dice5) => "Yacht".to_string(),
+        (Scoring::Generala, LineId::LargeStraight) => "Escalera".to_string(),
+        (Scoring::Generala, LineId::FullHouse) => "Full".to_string(),
+        (Scoring::Generala, LineId::FourKind) => "Poker".to_string(),
+        (Scoring::Generala, LineId::Dice5) => "Generala".to_string(),
+        (Scoring::Generala, LineId::DoubleDice5) => "Doble Generala".to_string(),
+        _ => get_long_name(zid),
+    }
+}
+
 pub fn get_short_name(zid: LineId) -> String {
     match zid {
         LineId::Ace => "1".to_string(),
@@ -34,32 +61,46 @@ pub fn get_short_name(zid: LineId) -> String {
         LineId::Five => "5".to_string(),
         LineId::Six => "6".to_string(),
 
+        LineId::OnePair => "1p".to_string(),
+        LineId::TwoPairs => "2p".to_string(),
+        LineId::ThreePairs => "3p".to_string(),
         LineId::ThreeKind => "3k".to_string(),
         LineId::FourKind => "4k".to_string(),
         LineId::SmallStraight => "ss".to_string(),
         LineId::LargeStraight => "ls".to_string(),
+        LineId::FullStraight => "fs".to_string(),
         LineId::FullHouse => "fh".to_string(),
+        LineId::Castle => "ca".to_string(),
+        LineId::Tower => "to".to_string(),
         LineId::Chance => "c".to_string(),
         LineId::Dice5 => "d".to_string(),
+        LineId::DoubleDice5 => "dd".to_string(),
         _ => "*ERROR".to_string(),
     }
 }
 
-pub fn get_id_by_short_name(s: &str) -> Result<LineId, ()> {
+pub fn get_id_by_short_name(s: &str) -> Option<LineId> {
     match s {
-        "1" => Ok(LineId::Ace),
-        "2" => Ok(LineId::Two),
-        "3" => Ok(LineId::Three),
-        "4" => Ok(LineId::Four),
-        "5" => Ok(LineId::Five),
-        "6" => Ok(LineId::Six),
-        "3k" => Ok(LineId::ThreeKind),
-        "4k" => Ok(LineId::FourKind),
-        "ss" => Ok(LineId::SmallStraight),
-        "ls" => Ok(LineId::LargeStraight),
-        "fh" => Ok(LineId::FullHouse),
-        "c" => Ok(LineId::Chance),
-        "d" => Ok(LineId::Dice5),
-        _ => Err(()),
+        "1" => Some(LineId::Ace),
+        "2" => Some(LineId::Two),
+        "3" => Some(LineId::Three),
+        "4" => Some(LineId::Four),
+        "5" => Some(LineId::Five),
+        "6" => Some(LineId::Six),
+        "1p" => Some(LineId::OnePair),
+        "2p" => Some(LineId::TwoPairs),
+        "3p" => Some(LineId::ThreePairs),
+        "3k" => Some(LineId::ThreeKind),
+        "4k" => Some(LineId::FourKind),
+        "ss" => Some(LineId::SmallStraight),
+        "ls" => Some(LineId::LargeStraight),
+        "fs" => Some(LineId::FullStraight),
+        "fh" => Some(LineId::FullHouse),
+        "ca" => Some(LineId::Castle),
+        "to" => Some(LineId::Tower),
+        "c" => Some(LineId::Chance),
+        "d" => Some(LineId::Dice5),
+        "dd" => Some(LineId::DoubleDice5),
+        _ => None,
     }
 }